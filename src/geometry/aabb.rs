@@ -0,0 +1,145 @@
+use std::f32;
+use std::mem;
+
+use super::{Point3D, Vector3D, Ray3D};
+
+#[derive(PartialEq, PartialOrd, Clone, Debug)]
+pub struct Aabb {
+    pub min: Point3D,
+    pub max: Point3D
+}
+
+impl Aabb {
+    pub fn new(min: &Point3D, max: &Point3D) -> Aabb {
+        Aabb {
+            min: min.clone(),
+            max: max.clone()
+        }
+    }
+
+    // A box that contains nothing, so that its union with any box is that box.
+    pub fn empty() -> Aabb {
+        Aabb {
+            min: Point3D::from_xyz(f32::INFINITY, f32::INFINITY, f32::INFINITY),
+            max: Point3D::from_xyz(f32::NEG_INFINITY, f32::NEG_INFINITY, f32::NEG_INFINITY)
+        }
+    }
+
+    pub fn from_points(points: &[Point3D]) -> Aabb {
+        points.iter().fold(Aabb::empty(), |bounds, point| bounds.expand(point))
+    }
+
+    pub fn union(box1: &Aabb, box2: &Aabb) -> Aabb {
+        Aabb {
+            min: Point3D::from_xyz(
+                box1.min.x.min(box2.min.x),
+                box1.min.y.min(box2.min.y),
+                box1.min.z.min(box2.min.z)
+            ),
+            max: Point3D::from_xyz(
+                box1.max.x.max(box2.max.x),
+                box1.max.y.max(box2.max.y),
+                box1.max.z.max(box2.max.z)
+            )
+        }
+    }
+
+    // The overlap of the two boxes, which is empty if they do not overlap.
+    pub fn intersection(box1: &Aabb, box2: &Aabb) -> Aabb {
+        Aabb {
+            min: Point3D::from_xyz(
+                box1.min.x.max(box2.min.x),
+                box1.min.y.max(box2.min.y),
+                box1.min.z.max(box2.min.z)
+            ),
+            max: Point3D::from_xyz(
+                box1.max.x.min(box2.max.x),
+                box1.max.y.min(box2.max.y),
+                box1.max.z.min(box2.max.z)
+            )
+        }
+    }
+
+    pub fn expand(&self, point: &Point3D) -> Aabb {
+        Aabb::union(self, &Aabb::new(point, point))
+    }
+
+    // Returns the box enlarged by `margin` on every side.
+    pub fn grow(&self, margin: f32) -> Aabb {
+        Aabb {
+            min: Point3D::from_xyz(self.min.x - margin, self.min.y - margin, self.min.z - margin),
+            max: Point3D::from_xyz(self.max.x + margin, self.max.y + margin, self.max.z + margin)
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x > self.max.x || self.min.y > self.max.y || self.min.z > self.max.z
+    }
+
+    pub fn contains(&self, point: &Point3D) -> bool {
+        point.x >= self.min.x && point.x <= self.max.x &&
+        point.y >= self.min.y && point.y <= self.max.y &&
+        point.z >= self.min.z && point.z <= self.max.z
+    }
+
+    pub fn size(&self) -> Vector3D {
+        if self.is_empty() {
+            return Vector3D::zero().clone();
+        }
+        Vector3D::between_points(&self.min, &self.max)
+    }
+
+    pub fn centroid(&self) -> Point3D {
+        Point3D::from_xyz(
+            0.5 * (self.min.x + self.max.x),
+            0.5 * (self.min.y + self.max.y),
+            0.5 * (self.min.z + self.max.z)
+        )
+    }
+
+    pub fn surface_area(&self) -> f32 {
+        let size = self.size();
+        2.0 * (size.x * size.y + size.y * size.z + size.z * size.x)
+    }
+
+    // Slab test: returns the distance at which the ray enters the box within
+    // its range. Rays starting inside the box enter it at `t_min`.
+    pub fn intersect(&self, ray: &Ray3D) -> Option<f32> {
+        self.intersect_interval(ray, ray.t_min, ray.t_max).map(|(t_enter, _)| t_enter)
+    }
+
+    // Slab test: returns the part of [`t_start`, `t_end`] along the ray that is
+    // inside the box.
+    pub fn intersect_interval(&self, ray: &Ray3D, t_start: f32, t_end: f32) -> Option<(f32, f32)> {
+        let mut t_min = t_start;
+        let mut t_max = t_end;
+
+        let origin = [ray.origin.x, ray.origin.y, ray.origin.z];
+        let direction = [ray.direction.x(), ray.direction.y(), ray.direction.z()];
+        let box_min = [self.min.x, self.min.y, self.min.z];
+        let box_max = [self.max.x, self.max.y, self.max.z];
+
+        for axis in 0..3 {
+            let inverse_direction = 1.0 / direction[axis];
+            let mut t0 = (box_min[axis] - origin[axis]) * inverse_direction;
+            let mut t1 = (box_max[axis] - origin[axis]) * inverse_direction;
+            if inverse_direction < 0.0 {
+                mem::swap(&mut t0, &mut t1);
+            }
+
+            // NaN comparisons are false, so a ray lying exactly in the plane
+            // of a slab keeps the current interval.
+            if t0 > t_min {
+                t_min = t0;
+            }
+            if t1 < t_max {
+                t_max = t1;
+            }
+            if t_min > t_max {
+                return None;
+            }
+        }
+
+        Some((t_min, t_max))
+    }
+}
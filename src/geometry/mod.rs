@@ -5,6 +5,8 @@ pub use self::vector::{Vector3D, AsVector};
 pub use self::direction::{Direction3D};
 pub use self::ray::{Ray3D};
 pub use self::matrix::{Matrix3D};
+pub use self::aabb::{Aabb};
+pub use self::transform::{Transform};
 
 #[macro_use]
 pub mod vector;
@@ -13,6 +15,9 @@ pub mod point;
 pub mod direction;
 pub mod ray;
 pub mod matrix;
+pub mod aabb;
+pub mod transform;
+pub mod polynomial;
 
 pub const EPSILON: f32 = 1.0E-9;
 pub const DEGREES_TO_RADIANS: f32 = PI / 180.0;
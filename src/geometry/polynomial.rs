@@ -0,0 +1,181 @@
+use std::cmp::{Ordering};
+use std::f64::consts::{PI};
+
+// Polynomial root finders for ray-surface intersection. They work in double
+// precision and return the real roots in ascending order.
+
+const ZERO_TOLERANCE: f64 = 1.0e-12;
+const NEWTON_ITERATIONS: usize = 2;
+
+// Solves a*x^2 + b*x + c = 0 without the cancellation of the textbook formula.
+// A double root is returned twice.
+pub fn solve_quadratic(a: f64, b: f64, c: f64) -> Option<(f64, f64)> {
+    if a.abs() < ZERO_TOLERANCE {
+        if b.abs() < ZERO_TOLERANCE {
+            return None;
+        }
+        let root = -c / b;
+        return Some((root, root));
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return None;
+    }
+
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let root1 = q / a;
+    let root2 = if q != 0.0 { c / q } else { root1 };
+
+    if root1 <= root2 {
+        Some((root1, root2))
+    } else {
+        Some((root2, root1))
+    }
+}
+
+// Solves x^3 + a*x^2 + b*x + c = 0.
+pub fn solve_normalized_cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    // Substitute x = y - a/3 to remove the quadratic term: y^3 + 3p*y + 2q = 0.
+    let a_squared = a * a;
+    let p = (-a_squared / 3.0 + b) / 3.0;
+    let q = (2.0 / 27.0 * a * a_squared - a * b / 3.0 + c) / 2.0;
+
+    let p_cubed = p * p * p;
+    let discriminant = q * q + p_cubed;
+
+    let mut roots = if discriminant.abs() < ZERO_TOLERANCE {
+        if q.abs() < ZERO_TOLERANCE {
+            vec![0.0]
+        } else {
+            let u = (-q).cbrt();
+            vec![2.0 * u, -u]
+        }
+    } else if discriminant < 0.0 {
+        // Three real roots.
+        let phi = (-q / (-p_cubed).sqrt()).clamp(-1.0, 1.0).acos() / 3.0;
+        let t = 2.0 * (-p).sqrt();
+        vec![t * phi.cos(), -t * (phi + PI / 3.0).cos(), -t * (phi - PI / 3.0).cos()]
+    } else {
+        let discriminant_root = discriminant.sqrt();
+        vec![(discriminant_root - q).cbrt() - (discriminant_root + q).cbrt()]
+    };
+
+    for root in &mut roots {
+        *root -= a / 3.0;
+    }
+    roots.sort_by(|x, y| x.partial_cmp(y).unwrap_or(Ordering::Equal));
+    roots
+}
+
+// Solves a*x^4 + b*x^3 + c*x^2 + d*x + e = 0 with Ferrari's method, then
+// refines each root with Newton's method to recover the precision lost in the
+// resolvent cubic.
+pub fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    if a.abs() < ZERO_TOLERANCE {
+        return Vec::new();
+    }
+
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // Substitute x = y - b/4 to remove the cubic term: y^4 + p*y^2 + q*y + r = 0.
+    let b_squared = b * b;
+    let p = -3.0 / 8.0 * b_squared + c;
+    let q = b_squared * b / 8.0 - b * c / 2.0 + d;
+    let r = -3.0 / 256.0 * b_squared * b_squared + b_squared * c / 16.0 - b * d / 4.0 + e;
+
+    let mut roots = Vec::with_capacity(4);
+    if r.abs() < ZERO_TOLERANCE {
+        // y * (y^3 + p*y + q) = 0
+        roots.push(0.0);
+        roots.extend(solve_normalized_cubic(0.0, p, q));
+    } else {
+        // Take one root of the resolvent cubic and split the quartic into two
+        // quadratics.
+        let resolvent = solve_normalized_cubic(-p / 2.0, -r, r * p / 2.0 - q * q / 8.0);
+        let z = resolvent[resolvent.len() - 1];
+
+        let u = z * z - r;
+        let v = 2.0 * z - p;
+        let u = if u.abs() < ZERO_TOLERANCE {
+            0.0
+        } else if u > 0.0 {
+            u.sqrt()
+        } else {
+            return Vec::new();
+        };
+        let v = if v.abs() < ZERO_TOLERANCE {
+            0.0
+        } else if v > 0.0 {
+            v.sqrt()
+        } else {
+            return Vec::new();
+        };
+
+        let v_signed = if q < 0.0 { -v } else { v };
+        if let Some((root1, root2)) = solve_quadratic(1.0, v_signed, z - u) {
+            roots.push(root1);
+            roots.push(root2);
+        }
+        if let Some((root1, root2)) = solve_quadratic(1.0, -v_signed, z + u) {
+            roots.push(root1);
+            roots.push(root2);
+        }
+    }
+
+    for root in &mut roots {
+        *root -= b / 4.0;
+        for _ in 0..NEWTON_ITERATIONS {
+            let x = *root;
+            let value = (((x + b) * x + c) * x + d) * x + e;
+            let derivative = ((4.0 * x + 3.0 * b) * x + 2.0 * c) * x + d;
+            if derivative.abs() > ZERO_TOLERANCE {
+                *root = x - value / derivative;
+            }
+        }
+    }
+    roots.sort_by(|x, y| x.partial_cmp(y).unwrap_or(Ordering::Equal));
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{solve_quartic};
+
+    fn assert_roots(actual: Vec<f64>, expected: &[f64], tolerance: f64) {
+        assert_eq!(actual.len(), expected.len(), "expected {:?}, got {:?}", expected, actual);
+        for (root, expected_root) in actual.iter().zip(expected) {
+            assert!((root - expected_root).abs() < tolerance, "expected {:?}, got {:?}", expected, actual);
+        }
+    }
+
+    #[test]
+    fn finds_four_real_roots() {
+        // (x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(solve_quartic(1.0, -10.0, 35.0, -50.0, 24.0), &[1.0, 2.0, 3.0, 4.0], 1.0e-9);
+    }
+
+    #[test]
+    fn finds_two_real_roots() {
+        // (x - 1)(x + 2)(x^2 + 1)
+        assert_roots(solve_quartic(1.0, 1.0, -1.0, 1.0, -2.0), &[-2.0, 1.0], 1.0e-9);
+    }
+
+    #[test]
+    fn finds_no_roots() {
+        // (x^2 + 1)(x^2 + 4)
+        assert_roots(solve_quartic(1.0, 0.0, 5.0, 0.0, 4.0), &[], 0.0);
+    }
+
+    #[test]
+    fn finds_a_repeated_root_twice() {
+        // (x - 1)^2 (x - 2)(x - 3)
+        assert_roots(solve_quartic(1.0, -7.0, 17.0, -17.0, 6.0), &[1.0, 1.0, 2.0, 3.0], 1.0e-6);
+    }
+
+    #[test]
+    fn scales_by_the_leading_coefficient() {
+        // 2(x - 1)(x - 2)(x - 3)(x - 4)
+        assert_roots(solve_quartic(2.0, -20.0, 70.0, -100.0, 48.0), &[1.0, 2.0, 3.0, 4.0], 1.0e-9);
+    }
+}
@@ -1,20 +1,35 @@
+use std::f32;
+
 use super::{Point3D, Direction3D};
 
+// A ray only hits surfaces at distances between `t_min` and `t_max`.
 #[derive(PartialEq, PartialOrd, Clone, Debug)]
 pub struct Ray3D {
     pub origin: Point3D,
-    pub direction: Direction3D
+    pub direction: Direction3D,
+    pub t_min: f32,
+    pub t_max: f32
 }
 
 impl Ray3D {
     pub fn new(origin: &Point3D, direction: &Direction3D) -> Ray3D {
+        Ray3D::with_range(origin, direction, 0.0, f32::INFINITY)
+    }
+
+    pub fn with_range(origin: &Point3D, direction: &Direction3D, t_min: f32, t_max: f32) -> Ray3D {
         Ray3D { 
             origin: origin.clone(),
-            direction: direction.clone()
+            direction: direction.clone(),
+            t_min: t_min,
+            t_max: t_max
         }
     }
     
     pub fn point(&self, distance: f32) -> Point3D {
         self.origin.translate_dist(&self.direction, distance)
     }
+
+    pub fn is_in_range(&self, distance: f32) -> bool {
+        distance >= self.t_min && distance <= self.t_max
+    }
 }
@@ -0,0 +1,313 @@
+use super::{DEGREES_TO_RADIANS, Point3D, Vector3D, AsVector, Direction3D, Ray3D, Aabb};
+
+// An affine transform stored as a 4x4 row-major matrix along with its inverse,
+// so that both directions are available without inverting at render time.
+#[derive(PartialEq, Clone, Debug)]
+pub struct Transform {
+    matrix: [[f32; 4]; 4],
+    inverse: [[f32; 4]; 4]
+}
+
+const IDENTITY: [[f32; 4]; 4] = [
+    [1.0, 0.0, 0.0, 0.0],
+    [0.0, 1.0, 0.0, 0.0],
+    [0.0, 0.0, 1.0, 0.0],
+    [0.0, 0.0, 0.0, 1.0]
+];
+
+impl Transform {
+    pub fn identity() -> Transform {
+        Transform {
+            matrix: IDENTITY,
+            inverse: IDENTITY
+        }
+    }
+
+    // Returns `None` if the matrix cannot be inverted.
+    pub fn from_matrix(matrix: &[[f32; 4]; 4]) -> Option<Transform> {
+        invert(matrix).map(|inverse| {
+            Transform {
+                matrix: *matrix,
+                inverse: inverse
+            }
+        })
+    }
+
+    pub fn translation<T: AsVector>(offset: &T) -> Transform {
+        let v = offset.as_vector();
+        Transform {
+            matrix: [
+                [1.0, 0.0, 0.0, v.x],
+                [0.0, 1.0, 0.0, v.y],
+                [0.0, 0.0, 1.0, v.z],
+                [0.0, 0.0, 0.0, 1.0]
+            ],
+            inverse: [
+                [1.0, 0.0, 0.0, -v.x],
+                [0.0, 1.0, 0.0, -v.y],
+                [0.0, 0.0, 1.0, -v.z],
+                [0.0, 0.0, 0.0, 1.0]
+            ]
+        }
+    }
+
+    // A rigid transform that maps the origin to `origin` and the y axis to
+    // `axis`. The x and z axes are chosen arbitrarily around it.
+    pub fn from_origin_axis(origin: &Point3D, axis: &Direction3D) -> Transform {
+        let helper = if axis.x().abs() < 0.9 {
+            Direction3D::unit_x()
+        } else {
+            Direction3D::unit_z()
+        };
+        let x = Vector3D::cross(helper, axis).to_unit();
+        let z = Vector3D::cross(&x, axis).to_unit();
+        let y = axis;
+
+        let rotation = [
+            [x.x(), y.x(), z.x(), 0.0],
+            [x.y(), y.y(), z.y(), 0.0],
+            [x.z(), y.z(), z.z(), 0.0],
+            [0.0, 0.0, 0.0, 1.0]
+        ];
+        Transform {
+            matrix: rotation,
+            inverse: transpose(&rotation)
+        }.then(&Transform::translation(&Vector3D::from_point(origin)))
+    }
+
+    // Counter-clockwise rotation by `angle` degrees when looking down `axis`
+    // towards the origin.
+    pub fn rotation(axis: &Direction3D, angle: f32) -> Transform {
+        let matrix = rotation_matrix(axis, angle * DEGREES_TO_RADIANS);
+        Transform {
+            matrix: matrix,
+            inverse: transpose(&matrix)
+        }
+    }
+
+    pub fn scale(x: f32, y: f32, z: f32) -> Transform {
+        Transform {
+            matrix: [
+                [x, 0.0, 0.0, 0.0],
+                [0.0, y, 0.0, 0.0],
+                [0.0, 0.0, z, 0.0],
+                [0.0, 0.0, 0.0, 1.0]
+            ],
+            inverse: [
+                [1.0 / x, 0.0, 0.0, 0.0],
+                [0.0, 1.0 / y, 0.0, 0.0],
+                [0.0, 0.0, 1.0 / z, 0.0],
+                [0.0, 0.0, 0.0, 1.0]
+            ]
+        }
+    }
+
+    // Returns the transform that applies `self` first and then `next`.
+    pub fn then(&self, next: &Transform) -> Transform {
+        Transform {
+            matrix: multiply(&next.matrix, &self.matrix),
+            inverse: multiply(&self.inverse, &next.inverse)
+        }
+    }
+
+    pub fn inverse(&self) -> Transform {
+        Transform {
+            matrix: self.inverse,
+            inverse: self.matrix
+        }
+    }
+
+    pub fn get_matrix(&self) -> &[[f32; 4]; 4] {
+        &self.matrix
+    }
+
+    pub fn transform_point(&self, point: &Point3D) -> Point3D {
+        let m = &self.matrix;
+        Point3D::from_xyz(
+            m[0][0] * point.x + m[0][1] * point.y + m[0][2] * point.z + m[0][3],
+            m[1][0] * point.x + m[1][1] * point.y + m[1][2] * point.z + m[1][3],
+            m[2][0] * point.x + m[2][1] * point.y + m[2][2] * point.z + m[2][3]
+        )
+    }
+
+    pub fn transform_vector<T: AsVector>(&self, vector: &T) -> Vector3D {
+        transform_vector(&self.matrix, vector.as_vector())
+    }
+
+    // Normals are transformed by the inverse transpose, so that they stay
+    // perpendicular to the surface under non-uniform scaling.
+    pub fn transform_normal<T: AsVector>(&self, normal: &T) -> Direction3D {
+        let n = normal.as_vector();
+        let m = &self.inverse;
+        Direction3D::from_xyz(
+            m[0][0] * n.x + m[1][0] * n.y + m[2][0] * n.z,
+            m[0][1] * n.x + m[1][1] * n.y + m[2][1] * n.z,
+            m[0][2] * n.x + m[1][2] * n.y + m[2][2] * n.z
+        )
+    }
+
+    // Returns the transformed ray and the factor by which the transform
+    // stretches distances along it: a distance `t` along the original ray is
+    // `t * scale` along the returned one. The ray's range is scaled to match.
+    pub fn transform_ray(&self, ray: &Ray3D) -> (Ray3D, f32) {
+        let direction = self.transform_vector(&ray.direction);
+        let scale = direction.magnitude();
+        let transformed = Ray3D::with_range(
+            &self.transform_point(&ray.origin),
+            &direction.to_unit(),
+            ray.t_min * scale,
+            ray.t_max * scale
+        );
+        (transformed, scale)
+    }
+
+    // Returns a box containing the transformed corners of `bounds`.
+    pub fn transform_bounds(&self, bounds: &Aabb) -> Aabb {
+        if bounds.is_empty() {
+            return bounds.clone();
+        }
+
+        let mut corners = Vec::with_capacity(8);
+        for &x in &[bounds.min.x, bounds.max.x] {
+            for &y in &[bounds.min.y, bounds.max.y] {
+                for &z in &[bounds.min.z, bounds.max.z] {
+                    corners.push(self.transform_point(&Point3D::from_xyz(x, y, z)));
+                }
+            }
+        }
+        Aabb::from_points(&corners)
+    }
+}
+
+fn transform_vector(m: &[[f32; 4]; 4], v: &Vector3D) -> Vector3D {
+    Vector3D::from_xyz(
+        m[0][0] * v.x + m[0][1] * v.y + m[0][2] * v.z,
+        m[1][0] * v.x + m[1][1] * v.y + m[1][2] * v.z,
+        m[2][0] * v.x + m[2][1] * v.y + m[2][2] * v.z
+    )
+}
+
+fn rotation_matrix(axis: &Direction3D, angle: f32) -> [[f32; 4]; 4] {
+    let (x, y, z) = (axis.x(), axis.y(), axis.z());
+    let (sin, cos) = angle.sin_cos();
+    let t = 1.0 - cos;
+
+    [
+        [t * x * x + cos, t * x * y - sin * z, t * x * z + sin * y, 0.0],
+        [t * x * y + sin * z, t * y * y + cos, t * y * z - sin * x, 0.0],
+        [t * x * z - sin * y, t * y * z + sin * x, t * z * z + cos, 0.0],
+        [0.0, 0.0, 0.0, 1.0]
+    ]
+}
+
+fn multiply(a: &[[f32; 4]; 4], b: &[[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    let mut result = [[0.0; 4]; 4];
+    for row in 0..4 {
+        for column in 0..4 {
+            result[row][column] = (0..4).map(|k| a[row][k] * b[k][column]).sum();
+        }
+    }
+    result
+}
+
+fn transpose(m: &[[f32; 4]; 4]) -> [[f32; 4]; 4] {
+    let mut result = [[0.0; 4]; 4];
+    for row in 0..4 {
+        for column in 0..4 {
+            result[row][column] = m[column][row];
+        }
+    }
+    result
+}
+
+// Gauss-Jordan elimination with partial pivoting.
+fn invert(m: &[[f32; 4]; 4]) -> Option<[[f32; 4]; 4]> {
+    let mut a = *m;
+    let mut result = IDENTITY;
+
+    for column in 0..4 {
+        let pivot = (column..4).fold(column, |best, row| {
+            if a[row][column].abs() > a[best][column].abs() { row } else { best }
+        });
+        if a[pivot][column].abs() < 1.0e-12 {
+            return None;
+        }
+        a.swap(column, pivot);
+        result.swap(column, pivot);
+
+        let scale = 1.0 / a[column][column];
+        for k in 0..4 {
+            a[column][k] *= scale;
+            result[column][k] *= scale;
+        }
+
+        for row in 0..4 {
+            if row != column {
+                let factor = a[row][column];
+                for k in 0..4 {
+                    a[row][k] -= factor * a[column][k];
+                    result[row][k] -= factor * result[column][k];
+                }
+            }
+        }
+    }
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use geometry::{Point3D, Vector3D, Direction3D};
+
+    use super::{Transform, IDENTITY};
+
+    // Scales non-uniformly, then rotates about a tilted axis and moves away
+    // from the origin.
+    fn placement() -> Transform {
+        Transform::scale(2.0, 0.5, 3.0)
+            .then(&Transform::rotation(&Direction3D::from_xyz(1.0, 2.0, 3.0), 40.0))
+            .then(&Transform::translation(&Vector3D::from_xyz(1.0, -2.0, 5.0)))
+    }
+
+    fn assert_matrix_near(actual: &[[f32; 4]; 4], expected: &[[f32; 4]; 4]) {
+        for row in 0..4 {
+            for column in 0..4 {
+                assert!((actual[row][column] - expected[row][column]).abs() < 1.0e-5, "expected {:?}, got {:?}", expected, actual);
+            }
+        }
+    }
+
+    #[test]
+    fn cancels_with_its_inverse() {
+        let transform = placement();
+        assert_matrix_near(transform.then(&transform.inverse()).get_matrix(), &IDENTITY);
+        assert_matrix_near(transform.inverse().then(&transform).get_matrix(), &IDENTITY);
+
+        let point = Point3D::from_xyz(0.3, -1.2, 4.0);
+        let round_trip = transform.inverse().transform_point(&transform.transform_point(&point));
+        assert!(Vector3D::between_points(&point, &round_trip).magnitude() < 1.0e-5, "got {:?}", round_trip);
+    }
+
+    #[test]
+    fn inverts_a_general_matrix() {
+        let transform = placement();
+        let inverted = Transform::from_matrix(transform.get_matrix()).unwrap().inverse();
+        assert_matrix_near(inverted.get_matrix(), transform.inverse().get_matrix());
+        assert!(Transform::from_matrix(Transform::scale(1.0, 0.0, 1.0).get_matrix()).is_none());
+    }
+
+    #[test]
+    fn keeps_normals_perpendicular_to_the_surface() {
+        let transform = placement();
+        let normal = Direction3D::from_xyz(1.0, 1.0, 0.0);
+        for tangent in &[Vector3D::from_xyz(1.0, -1.0, 0.0), Vector3D::from_xyz(0.0, 0.0, 1.0), Vector3D::from_xyz(2.0, -2.0, 5.0)] {
+            let transformed_tangent = transform.transform_vector(tangent).to_unit();
+            let transformed_normal = transform.transform_normal(&normal);
+            assert!(Vector3D::dot(&transformed_normal, &transformed_tangent).abs() < 1.0e-5, "normal {:?} is not perpendicular to {:?}", transformed_normal, transformed_tangent);
+        }
+
+        // Transforming the normal like a vector would tilt it.
+        let tilted = transform.transform_vector(&normal).to_unit();
+        assert!(Vector3D::dot(&tilted, &transform.transform_vector(&Vector3D::from_xyz(1.0, -1.0, 0.0)).to_unit()).abs() > 0.1);
+    }
+}
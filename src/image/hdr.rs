@@ -0,0 +1,133 @@
+use std::fs::{OpenOptions};
+use std::io::{BufWriter, Write, Result};
+use std::path::{Path};
+
+use color::{ColorRGB};
+use table::{Table};
+
+const MIN_RUN_LENGTH: usize = 4;
+const MAX_RUN_LENGTH: usize = 127;
+const MAX_DUMP_LENGTH: usize = 128;
+
+// Radiance RGBE: each pixel stores an 8-bit mantissa per channel and a shared
+// 8-bit exponent. Scanlines are run-length encoded one channel at a time when
+// the width allows it.
+#[derive(Debug)]
+pub struct HDRImage {
+    file_name: String
+}
+
+impl HDRImage {
+    pub fn new(file_name: &str) -> HDRImage {
+        HDRImage {
+            file_name: file_name.to_string()
+        }
+    }
+
+    pub fn save(&self, image: &Table<ColorRGB>) -> Result<()> {
+        let file_path = Path::new(&self.file_name);
+
+        let file = try!(OpenOptions::new().create(true).write(true).truncate(true).open(file_path));
+        let mut writer = BufWriter::new(file);
+
+        let (width, height) = image.get_dimensions();
+
+        try!(writeln!(&mut writer, "#?RADIANCE"));
+        try!(writeln!(&mut writer, "FORMAT=32-bit_rle_rgbe"));
+        try!(writeln!(&mut writer, ""));
+        try!(writeln!(&mut writer, "-Y {} +X {}", height, width));
+
+        let use_rle = (8..=0x7FFF).contains(&width);
+        let mut scanline = Vec::with_capacity(width * 4);
+        for row in 0..height {
+            scanline.clear();
+            for column in 0..width {
+                scanline.extend_from_slice(&convert_to_rgbe(&image[(row, column)]));
+            }
+
+            if use_rle {
+                try!(writer.write_all(&[2, 2, (width >> 8) as u8, (width & 0xFF) as u8]));
+                let mut component = Vec::with_capacity(width);
+                for channel in 0..4 {
+                    component.clear();
+                    component.extend(scanline.iter().skip(channel).step_by(4));
+                    try!(write_run_length_encoded(&mut writer, &component));
+                }
+            } else {
+                try!(writer.write_all(&scanline));
+            }
+        }
+        writer.flush()
+    }
+}
+
+fn convert_to_rgbe(color: &ColorRGB) -> [u8; 4] {
+    let red = color.red.max(0.0);
+    let green = color.green.max(0.0);
+    let blue = color.blue.max(0.0);
+
+    let value = red.max(green).max(blue);
+    if value < 1.0e-32 {
+        return [0, 0, 0, 0];
+    }
+
+    // Split `value` into a mantissa in [0.5, 1) and a power of two.
+    let mut exponent = value.log2().floor() as i32 + 1;
+    let mut mantissa = value / 2.0f32.powi(exponent);
+    if mantissa >= 1.0 {
+        mantissa *= 0.5;
+        exponent += 1;
+    } else if mantissa < 0.5 {
+        mantissa *= 2.0;
+        exponent -= 1;
+    }
+
+    let scale = mantissa * 256.0 / value;
+    [
+        (red * scale) as u8,
+        (green * scale) as u8,
+        (blue * scale) as u8,
+        (exponent + 128) as u8
+    ]
+}
+
+fn write_run_length_encoded<W: Write>(writer: &mut W, data: &[u8]) -> Result<()> {
+    let length = data.len();
+    let mut current = 0;
+
+    while current < length {
+        // Find the start of the next run that is long enough to be worth encoding.
+        let mut run_start = current;
+        let mut run_count = 0;
+        let mut previous_run_count = 0;
+        while run_count < MIN_RUN_LENGTH && run_start < length {
+            run_start += run_count;
+            previous_run_count = run_count;
+            run_count = 1;
+            while run_start + run_count < length && run_count < MAX_RUN_LENGTH && data[run_start] == data[run_start + run_count] {
+                run_count += 1;
+            }
+        }
+
+        // A short run immediately before the long one is still written as a run.
+        if previous_run_count > 1 && previous_run_count == run_start - current {
+            try!(writer.write_all(&[(128 + previous_run_count) as u8, data[current]]));
+            current = run_start;
+        }
+
+        // Dump the bytes that are not part of a run.
+        while current < run_start {
+            let count = MAX_DUMP_LENGTH.min(run_start - current);
+            try!(writer.write_all(&[count as u8]));
+            try!(writer.write_all(&data[current..current + count]));
+            current += count;
+        }
+
+        if run_count >= MIN_RUN_LENGTH {
+            try!(writer.write_all(&[(128 + run_count) as u8, data[run_start]]));
+            current += run_count;
+        }
+    }
+
+    Ok(())
+}
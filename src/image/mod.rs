@@ -0,0 +1,55 @@
+use std::io::{Error, ErrorKind, Result};
+use std::path::{Path};
+
+use color::{ColorRGB};
+use table::{Table};
+
+pub use self::ppm::{PPMImage};
+pub use self::png::{PNGImage, BitDepth};
+pub use self::pfm::{PFMImage};
+pub use self::hdr::{HDRImage};
+pub use self::tone_map::{ToneMapping, ToneMapOperator, Clamp, Reinhard, Filmic, TransferFunction};
+
+mod ppm;
+mod png;
+mod pfm;
+mod hdr;
+mod tone_map;
+mod zlib;
+
+// Low dynamic range formats are tone mapped first, while high dynamic range
+// formats receive the linear radiance unchanged.
+pub fn save_image(file_name: &str, image: &Table<ColorRGB>, bit_depth: BitDepth, tone_mapping: &ToneMapping) -> Result<()> {
+    let extension = match Path::new(file_name).extension() {
+        Some(extension) => extension.to_string_lossy().to_ascii_lowercase(),
+        None => String::new()
+    };
+
+    match &*extension {
+        "ppm" => PPMImage::new(file_name).save(&tone_mapping.apply(image)),
+        "png" => PNGImage::new(file_name, bit_depth).save(&tone_mapping.apply(image)),
+        "pfm" => PFMImage::new(file_name).save(image),
+        "hdr" => HDRImage::new(file_name).save(image),
+        _ => Err(Error::new(ErrorKind::InvalidInput, format!("unsupported image format `.{}`", extension)))
+    }
+}
+
+pub fn load_image(file_name: &str) -> Result<Table<ColorRGB>> {
+    let extension = match Path::new(file_name).extension() {
+        Some(extension) => extension.to_string_lossy().to_ascii_lowercase(),
+        None => String::new()
+    };
+
+    match &*extension {
+        "ppm" | "pgm" | "pnm" => PPMImage::new(file_name).load(),
+        _ => Err(Error::new(ErrorKind::InvalidInput, format!("unsupported image format `.{}`", extension)))
+    }
+}
+
+fn convert_to_u8(value: f32) -> u8 {
+    (value * 255.0).clamp(0.0, 255.0) as u8
+}
+
+fn convert_to_u16(value: f32) -> u16 {
+    (value * 65535.0).clamp(0.0, 65535.0) as u16
+}
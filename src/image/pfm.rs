@@ -0,0 +1,56 @@
+use std::fs::{OpenOptions};
+use std::io::{BufWriter, Write, Result};
+use std::path::{Path};
+
+use color::{ColorRGB};
+use table::{Table};
+
+// Portable Float Map: a PPM-like header followed by raw 32-bit floats. A
+// negative scale marks the data as little-endian, and rows are stored from the
+// bottom of the image to the top.
+#[derive(Debug)]
+pub struct PFMImage {
+    file_name: String
+}
+
+impl PFMImage {
+    pub fn new(file_name: &str) -> PFMImage {
+        PFMImage {
+            file_name: file_name.to_string()
+        }
+    }
+
+    pub fn save(&self, image: &Table<ColorRGB>) -> Result<()> {
+        let file_path = Path::new(&self.file_name);
+
+        let file = try!(OpenOptions::new().create(true).write(true).truncate(true).open(file_path));
+        let mut writer = BufWriter::new(file);
+
+        let (width, height) = image.get_dimensions();
+
+        try!(writeln!(&mut writer, "PF"));
+        try!(writeln!(&mut writer, "{} {}", width, height));
+        try!(writeln!(&mut writer, "-1.0"));
+
+        let mut row_bytes = Vec::with_capacity(width * 12);
+        for row in (0..height).rev() {
+            row_bytes.clear();
+            for column in 0..width {
+                let pixel = &image[(row, column)];
+                push_f32_le(&mut row_bytes, pixel.red);
+                push_f32_le(&mut row_bytes, pixel.green);
+                push_f32_le(&mut row_bytes, pixel.blue);
+            }
+            try!(writer.write_all(&row_bytes));
+        }
+        writer.flush()
+    }
+}
+
+fn push_f32_le(bytes: &mut Vec<u8>, value: f32) {
+    let bits = value.to_bits();
+    bytes.push(bits as u8);
+    bytes.push((bits >> 8) as u8);
+    bytes.push((bits >> 16) as u8);
+    bytes.push((bits >> 24) as u8);
+}
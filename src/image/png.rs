@@ -0,0 +1,245 @@
+use std::fs::{OpenOptions};
+use std::io::{BufWriter, Write, Result};
+use std::path::{Path};
+
+use color::{ColorRGB};
+use table::{Table};
+
+use super::{convert_to_u8, convert_to_u16};
+use super::zlib;
+
+static PNG_SIGNATURE: [u8; 8] = [0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A];
+
+const COLOR_TYPE_RGB: u8 = 2;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BitDepth {
+    Eight,
+    Sixteen
+}
+
+#[derive(Debug)]
+pub struct PNGImage {
+    file_name: String,
+    bit_depth: BitDepth
+}
+
+impl PNGImage {
+    pub fn new(file_name: &str, bit_depth: BitDepth) -> PNGImage {
+        PNGImage {
+            file_name: file_name.to_string(),
+            bit_depth: bit_depth
+        }
+    }
+
+    pub fn save(&self, image: &Table<ColorRGB>) -> Result<()> {
+        let file_path = Path::new(&self.file_name);
+
+        let file = try!(OpenOptions::new().create(true).write(true).truncate(true).open(file_path));
+        let mut writer = BufWriter::new(file);
+
+        let (width, height) = image.get_dimensions();
+        let bits_per_sample = match self.bit_depth {
+            BitDepth::Eight => 8,
+            BitDepth::Sixteen => 16
+        };
+
+        let mut header = Vec::with_capacity(13);
+        push_u32(&mut header, width as u32);
+        push_u32(&mut header, height as u32);
+        header.push(bits_per_sample);
+        header.push(COLOR_TYPE_RGB);
+        header.push(0); // compression method: deflate
+        header.push(0); // filter method: adaptive
+        header.push(0); // interlace method: none
+
+        let scanlines = self.filter_scanlines(image);
+        let compressed = zlib::compress(&scanlines);
+
+        try!(writer.write_all(&PNG_SIGNATURE));
+        try!(write_chunk(&mut writer, b"IHDR", &header));
+        try!(write_chunk(&mut writer, b"IDAT", &compressed));
+        try!(write_chunk(&mut writer, b"IEND", &[]));
+        writer.flush()
+    }
+
+    fn filter_scanlines(&self, image: &Table<ColorRGB>) -> Vec<u8> {
+        let (width, height) = image.get_dimensions();
+        let bytes_per_pixel = match self.bit_depth {
+            BitDepth::Eight => 3,
+            BitDepth::Sixteen => 6
+        };
+        let row_length = width * bytes_per_pixel;
+
+        let mut raw = Vec::with_capacity(row_length * height);
+        for pixel in image {
+            match self.bit_depth {
+                BitDepth::Eight => {
+                    raw.push(convert_to_u8(pixel.red));
+                    raw.push(convert_to_u8(pixel.green));
+                    raw.push(convert_to_u8(pixel.blue));
+                },
+                BitDepth::Sixteen => {
+                    push_u16(&mut raw, convert_to_u16(pixel.red));
+                    push_u16(&mut raw, convert_to_u16(pixel.green));
+                    push_u16(&mut raw, convert_to_u16(pixel.blue));
+                }
+            }
+        }
+
+        // Pick the filter for each row that minimizes the sum of absolute
+        // differences, which is the heuristic suggested by the PNG spec.
+        let zero_row = vec![0u8; row_length];
+        let mut filtered = Vec::with_capacity((row_length + 1) * height);
+        let mut candidate = vec![0u8; row_length];
+        let mut best = vec![0u8; row_length];
+        for row in 0..height {
+            let current = &raw[row * row_length..(row + 1) * row_length];
+            let previous = if row > 0 {
+                &raw[(row - 1) * row_length..row * row_length]
+            } else {
+                &zero_row[..]
+            };
+
+            let mut best_filter = 0;
+            let mut best_score = !0;
+            for filter in 0..5 {
+                apply_filter(filter, current, previous, bytes_per_pixel, &mut candidate);
+                let score = candidate.iter().fold(0usize, |sum, &value| sum + (value as i8).unsigned_abs() as usize);
+                if score < best_score {
+                    best_score = score;
+                    best_filter = filter;
+                    best.copy_from_slice(&candidate);
+                }
+            }
+
+            filtered.push(best_filter);
+            filtered.extend_from_slice(&best);
+        }
+
+        filtered
+    }
+}
+
+fn apply_filter(filter: u8, current: &[u8], previous: &[u8], bytes_per_pixel: usize, output: &mut [u8]) {
+    for index in 0..current.len() {
+        let left = if index >= bytes_per_pixel { current[index - bytes_per_pixel] } else { 0 };
+        let up = previous[index];
+        let up_left = if index >= bytes_per_pixel { previous[index - bytes_per_pixel] } else { 0 };
+
+        let predictor = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            _ => paeth_predictor(left, up, up_left)
+        };
+        output[index] = current[index].wrapping_sub(predictor);
+    }
+}
+
+fn paeth_predictor(left: u8, up: u8, up_left: u8) -> u8 {
+    let p = left as i16 + up as i16 - up_left as i16;
+    let p_left = (p - left as i16).abs();
+    let p_up = (p - up as i16).abs();
+    let p_up_left = (p - up_left as i16).abs();
+
+    if p_left <= p_up && p_left <= p_up_left {
+        left
+    } else if p_up <= p_up_left {
+        up
+    } else {
+        up_left
+    }
+}
+
+fn write_chunk<W: Write>(writer: &mut W, chunk_type: &[u8; 4], data: &[u8]) -> Result<()> {
+    let mut length = Vec::with_capacity(4);
+    push_u32(&mut length, data.len() as u32);
+
+    let mut crc = Crc32::new();
+    crc.update(chunk_type);
+    crc.update(data);
+    let mut checksum = Vec::with_capacity(4);
+    push_u32(&mut checksum, crc.finish());
+
+    try!(writer.write_all(&length));
+    try!(writer.write_all(chunk_type));
+    try!(writer.write_all(data));
+    writer.write_all(&checksum)
+}
+
+fn push_u16(bytes: &mut Vec<u8>, value: u16) {
+    bytes.push((value >> 8) as u8);
+    bytes.push(value as u8);
+}
+
+fn push_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.push((value >> 24) as u8);
+    bytes.push((value >> 16) as u8);
+    bytes.push((value >> 8) as u8);
+    bytes.push(value as u8);
+}
+
+struct Crc32 {
+    table: [u32; 256],
+    value: u32
+}
+
+impl Crc32 {
+    fn new() -> Crc32 {
+        let mut table = [0u32; 256];
+        for (n, entry) in table.iter_mut().enumerate() {
+            let mut c = n as u32;
+            for _ in 0..8 {
+                c = if c & 1 != 0 {
+                    0xEDB88320 ^ (c >> 1)
+                } else {
+                    c >> 1
+                };
+            }
+            *entry = c;
+        }
+
+        Crc32 {
+            table: table,
+            value: 0xFFFFFFFF
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        for &byte in data {
+            self.value = self.table[((self.value ^ byte as u32) & 0xFF) as usize] ^ (self.value >> 8);
+        }
+    }
+
+    fn finish(&self) -> u32 {
+        self.value ^ 0xFFFFFFFF
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Crc32};
+
+    fn crc32(data: &[u8]) -> u32 {
+        let mut crc = Crc32::new();
+        crc.update(data);
+        crc.finish()
+    }
+
+    #[test]
+    fn computes_crc32_reference_values() {
+        assert_eq!(crc32(b""), 0);
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+        assert_eq!(crc32(b"IEND"), 0xAE426082);
+    }
+
+    #[test]
+    fn computes_crc32_across_updates() {
+        let mut crc = Crc32::new();
+        crc.update(b"1234");
+        crc.update(b"56789");
+        assert_eq!(crc.finish(), 0xCBF43926);
+    }
+}
@@ -0,0 +1,245 @@
+use std::fs::{File, OpenOptions};
+use std::io::{Read, Write, Error, ErrorKind, Result};
+use std::path::{Path};
+
+use color::{ColorRGB};
+use table::{Table};
+
+use super::{convert_to_u8};
+
+#[derive(Debug)]
+pub struct PPMImage {
+    file_name: String
+}
+
+impl PPMImage {
+    pub fn new(file_name: &str) -> PPMImage {
+        PPMImage {
+            file_name: file_name.to_string()
+        }
+    }
+    
+    pub fn save(&self, image: &Table<ColorRGB>) -> Result<()> {
+        let file_path = Path::new(&self.file_name);
+
+        let mut file = try!(OpenOptions::new().create(true).write(true).truncate(true).open(&file_path));
+
+        let (width, height) = image.get_dimensions();
+
+        try!(writeln!(&mut file, "P6"));
+        try!(writeln!(&mut file, "{} {}", width, height));
+        try!(writeln!(&mut file, "255"));
+
+        for pixel in image {                
+            let red = convert_to_u8(pixel.red);
+            let green = convert_to_u8(pixel.green);
+            let blue = convert_to_u8(pixel.blue);
+            
+            let pixel_bytes = vec![red, green, blue];
+            try!(file.write(&pixel_bytes));
+        }
+        Ok(())
+    }
+
+    // Reads binary (P5, P6) and ASCII (P2, P3) grayscale and color files with
+    // any maxval up to 65535. Samples are scaled to [0, 1].
+    pub fn load(&self) -> Result<Table<ColorRGB>> {
+        let file_path = Path::new(&self.file_name);
+
+        let mut file = try!(File::open(file_path));
+        let mut data = Vec::new();
+        try!(file.read_to_end(&mut data));
+        decode(&data)
+    }
+}
+
+// Decodes the contents of a file, for `load`.
+fn decode(data: &[u8]) -> Result<Table<ColorRGB>> {
+    let mut reader = HeaderReader::new(data);
+
+    let magic = try!(reader.read_token());
+    let (is_color, is_binary) = match &*magic {
+        "P2" => (false, false),
+        "P3" => (true, false),
+        "P5" => (false, true),
+        "P6" => (true, true),
+        _ => return Err(invalid_data(format!("unsupported magic number `{}`", magic)))
+    };
+
+    let width = try!(reader.read_number("width"));
+    let height = try!(reader.read_number("height"));
+    let max_value = try!(reader.read_number("maxval"));
+    if width == 0 || height == 0 {
+        return Err(invalid_data(format!("invalid image dimensions {} x {}", width, height)));
+    }
+    if max_value == 0 || max_value > 65535 {
+        return Err(invalid_data(format!("invalid maxval {}", max_value)));
+    }
+
+    let channels = if is_color { 3 } else { 1 };
+    let sample_count = match width.checked_mul(height).and_then(|pixels| pixels.checked_mul(channels)) {
+        Some(sample_count) => sample_count,
+        None => return Err(invalid_data(format!("image dimensions {} x {} are too large", width, height)))
+    };
+    let scale = 1.0 / (max_value as f32);
+
+    // The header is not trusted with the size of the image until the data
+    // to fill it has been found.
+    let mut samples = Vec::new();
+    if is_binary {
+        // Exactly one whitespace character separates the header from the data.
+        try!(reader.skip_single_whitespace());
+        let bytes_per_sample = if max_value < 256 { 1 } else { 2 };
+        let data = reader.remaining();
+        if data.len() / bytes_per_sample < sample_count {
+            return Err(invalid_data(format!("truncated image data: expected {} samples, found {}", sample_count, data.len() / bytes_per_sample)));
+        }
+        samples.reserve(sample_count);
+        for index in 0..sample_count {
+            let value = if bytes_per_sample == 1 {
+                data[index] as usize
+            } else {
+                ((data[2 * index] as usize) << 8) | (data[2 * index + 1] as usize)
+            };
+            samples.push(try!(check_sample(value, max_value)));
+        }
+    } else {
+        for _ in 0..sample_count {
+            let token = match reader.read_token() {
+                Ok(token) => token,
+                Err(_) => return Err(invalid_data(format!("truncated image data: expected {} samples, found {}", sample_count, samples.len())))
+            };
+            let value = match token.parse::<usize>() {
+                Ok(value) => value,
+                Err(_) => return Err(invalid_data(format!("invalid sample `{}`", token)))
+            };
+            samples.push(try!(check_sample(value, max_value)));
+        }
+    }
+
+    let mut image = Table::from_elem((width, height), ColorRGB::black().clone());
+    for (pixel, sample) in image.iter_mut().zip(samples.chunks(channels)) {
+        *pixel = if is_color {
+            ColorRGB::from_rgb(sample[0] as f32 * scale, sample[1] as f32 * scale, sample[2] as f32 * scale)
+        } else {
+            let gray = sample[0] as f32 * scale;
+            ColorRGB::from_rgb(gray, gray, gray)
+        };
+    }
+    Ok(image)
+}
+
+fn check_sample(value: usize, max_value: usize) -> Result<usize> {
+    if value > max_value {
+        Err(invalid_data(format!("sample value {} exceeds maxval {}", value, max_value)))
+    } else {
+        Ok(value)
+    }
+}
+
+fn invalid_data(message: String) -> Error {
+    Error::new(ErrorKind::InvalidData, message)
+}
+
+struct HeaderReader<'a> {
+    data: &'a [u8],
+    position: usize
+}
+
+impl<'a> HeaderReader<'a> {
+    fn new(data: &'a [u8]) -> HeaderReader<'a> {
+        HeaderReader {
+            data: data,
+            position: 0
+        }
+    }
+
+    fn remaining(&self) -> &'a [u8] {
+        &self.data[self.position..]
+    }
+
+    // Skips whitespace and `#` comments, then returns the next
+    // whitespace-delimited token.
+    fn read_token(&mut self) -> Result<String> {
+        loop {
+            match self.data.get(self.position) {
+                Some(&b'#') => {
+                    while self.position < self.data.len() && self.data[self.position] != b'\n' {
+                        self.position += 1;
+                    }
+                },
+                Some(byte) if (*byte as char).is_whitespace() => self.position += 1,
+                Some(_) => break,
+                None => return Err(invalid_data("unexpected end of header".to_string()))
+            }
+        }
+
+        let start = self.position;
+        while self.position < self.data.len() && !(self.data[self.position] as char).is_whitespace() && self.data[self.position] != b'#' {
+            self.position += 1;
+        }
+        Ok(String::from_utf8_lossy(&self.data[start..self.position]).into_owned())
+    }
+
+    fn read_number(&mut self, name: &str) -> Result<usize> {
+        let token = try!(self.read_token());
+        match token.parse::<usize>() {
+            Ok(value) => Ok(value),
+            Err(_) => Err(invalid_data(format!("invalid {} `{}`", name, token)))
+        }
+    }
+
+    fn skip_single_whitespace(&mut self) -> Result<()> {
+        match self.data.get(self.position) {
+            Some(byte) if (*byte as char).is_whitespace() => {
+                self.position += 1;
+                Ok(())
+            },
+            _ => Err(invalid_data("missing whitespace after header".to_string()))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{ErrorKind};
+
+    use super::{decode};
+
+    fn assert_invalid(data: &[u8]) {
+        match decode(data) {
+            Ok(_) => panic!("expected an error"),
+            Err(error) => assert_eq!(error.kind(), ErrorKind::InvalidData)
+        }
+    }
+
+    #[test]
+    fn reads_ascii_color() {
+        let image = decode(b"P3\n2 1 255\n255 0 0  0 0 255\n").unwrap();
+        assert_eq!(image.get_dimensions(), (2, 1));
+        let pixels: Vec<_> = image.iter().collect();
+        assert_eq!((pixels[0].red, pixels[0].blue), (1.0, 0.0));
+        assert_eq!((pixels[1].red, pixels[1].blue), (0.0, 1.0));
+    }
+
+    #[test]
+    fn reads_binary_gray_with_two_byte_samples() {
+        let image = decode(b"P5 1 1 65535\n\xff\xff").unwrap();
+        assert_eq!(image.iter().next().unwrap().green, 1.0);
+    }
+
+    #[test]
+    fn rejects_huge_ascii_image_without_data() {
+        assert_invalid(b"P3\n100000 100000 255\n1 2 3\n");
+    }
+
+    #[test]
+    fn rejects_dimensions_that_overflow() {
+        assert_invalid(b"P6\n4294967296 4294967296 255\n\x00\x00\x00");
+    }
+
+    #[test]
+    fn rejects_truncated_binary_data() {
+        assert_invalid(b"P6\n2 2 255\n\x00\x00\x00");
+    }
+}
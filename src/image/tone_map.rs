@@ -0,0 +1,141 @@
+use std::fmt::{Debug};
+
+use color::{ColorRGB};
+use table::{Table};
+
+// Converts unbounded linear radiance into the [0, 1] range expected by the
+// 8-bit and 16-bit image writers.
+pub trait ToneMapOperator: Debug + Sync + Send {
+    fn map(&self, color: &ColorRGB) -> ColorRGB;
+}
+
+// Clips every channel to [0, 1], which is what the writers used to do.
+#[derive(Clone, Debug)]
+pub struct Clamp;
+
+impl ToneMapOperator for Clamp {
+    fn map(&self, color: &ColorRGB) -> ColorRGB {
+        ColorRGB::from_rgb(
+            color.red.clamp(0.0, 1.0),
+            color.green.clamp(0.0, 1.0),
+            color.blue.clamp(0.0, 1.0)
+        )
+    }
+}
+
+// Extended Reinhard operator applied to luminance, so hues are preserved.
+// Luminance at or above `white_point` maps to 1.0.
+#[derive(Clone, Debug)]
+pub struct Reinhard {
+    white_point: f32
+}
+
+impl Reinhard {
+    pub fn new(white_point: f32) -> Reinhard {
+        Reinhard {
+            white_point: white_point
+        }
+    }
+}
+
+impl ToneMapOperator for Reinhard {
+    fn map(&self, color: &ColorRGB) -> ColorRGB {
+        let luminance = 0.2126 * color.red + 0.7152 * color.green + 0.0722 * color.blue;
+        if luminance <= 0.0 {
+            return ColorRGB::black().clone();
+        }
+
+        let white_squared = self.white_point * self.white_point;
+        let mapped = luminance * (1.0 + luminance / white_squared) / (1.0 + luminance);
+        Clamp.map(&(color * (mapped / luminance)))
+    }
+}
+
+// Krzysztof Narkowicz's curve fit of the ACES filmic tone curve.
+#[derive(Clone, Debug)]
+pub struct Filmic;
+
+impl ToneMapOperator for Filmic {
+    fn map(&self, color: &ColorRGB) -> ColorRGB {
+        ColorRGB::from_rgb(
+            aces_curve(color.red),
+            aces_curve(color.green),
+            aces_curve(color.blue)
+        )
+    }
+}
+
+fn aces_curve(value: f32) -> f32 {
+    let x = value.max(0.0);
+    let mapped = (x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14);
+    mapped.clamp(0.0, 1.0)
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum TransferFunction {
+    Linear,
+    SRGB,
+    Gamma(f32)
+}
+
+impl TransferFunction {
+    pub fn encode(&self, value: f32) -> f32 {
+        match *self {
+            TransferFunction::Linear => value,
+            TransferFunction::SRGB => {
+                if value <= 0.0031308 {
+                    12.92 * value
+                } else {
+                    1.055 * value.powf(1.0 / 2.4) - 0.055
+                }
+            },
+            TransferFunction::Gamma(gamma) => value.powf(1.0 / gamma)
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ToneMapping {
+    exposure: f32,
+    operator: Box<ToneMapOperator>,
+    transfer_function: TransferFunction
+}
+
+impl ToneMapping {
+    // `exposure` is measured in stops: each step doubles the brightness.
+    pub fn new(exposure: f32, operator: Box<ToneMapOperator>, transfer_function: TransferFunction) -> ToneMapping {
+        ToneMapping {
+            exposure: exposure,
+            operator: operator,
+            transfer_function: transfer_function
+        }
+    }
+
+    // Matches the original output: no exposure change, clipping and no encoding.
+    pub fn linear() -> ToneMapping {
+        ToneMapping::new(0.0, Box::new(Clamp), TransferFunction::Linear)
+    }
+
+    pub fn map(&self, color: &ColorRGB) -> ColorRGB {
+        let exposed = if self.exposure != 0.0 {
+            color * 2.0f32.powf(self.exposure)
+        } else {
+            color.clone()
+        };
+        let mapped = self.operator.map(&exposed);
+
+        ColorRGB::from_rgb(
+            self.transfer_function.encode(mapped.red),
+            self.transfer_function.encode(mapped.green),
+            self.transfer_function.encode(mapped.blue)
+        )
+    }
+
+    pub fn apply(&self, image: &Table<ColorRGB>) -> Table<ColorRGB> {
+        let mut result = Table::from_elem(image.get_dimensions(), ColorRGB::black().clone());
+        for (mapped, color) in result.iter_mut().zip(image.iter()) {
+            *mapped = self.map(color);
+        }
+        result
+    }
+}
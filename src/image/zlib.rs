@@ -0,0 +1,369 @@
+// A small zlib (RFC 1950) encoder using deflate (RFC 1951) with LZ77 matching
+// and the fixed Huffman codes, which is all that PNG output needs.
+
+const WINDOW_SIZE: usize = 32768;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+const MAX_CHAIN: usize = 64;
+
+const HASH_BITS: usize = 15;
+const HASH_SIZE: usize = 1 << HASH_BITS;
+const NO_POSITION: usize = !0;
+
+static LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31,
+    35, 43, 51, 59, 67, 83, 99, 115, 131, 163, 195, 227, 258
+];
+static LENGTH_EXTRA_BITS: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2,
+    3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0
+];
+static DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193,
+    257, 385, 513, 769, 1025, 1537, 2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577
+];
+static DISTANCE_EXTRA_BITS: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6,
+    7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13, 13
+];
+
+pub fn compress(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::new();
+
+    // CMF: deflate with a 32K window, FLG: default compression level.
+    writer.bytes.push(0x78);
+    writer.bytes.push(0x9C);
+
+    // A single final block using the fixed Huffman codes.
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    let mut head = vec![NO_POSITION; HASH_SIZE];
+    let mut previous = vec![NO_POSITION; WINDOW_SIZE];
+
+    let mut position = 0;
+    while position < data.len() {
+        let (length, distance) = find_match(data, position, &head, &previous);
+
+        if length >= MIN_MATCH {
+            write_length(&mut writer, length);
+            write_distance(&mut writer, distance);
+            for index in position..position + length {
+                insert_hash(data, index, &mut head, &mut previous);
+            }
+            position += length;
+        } else {
+            write_literal(&mut writer, data[position] as u16);
+            insert_hash(data, position, &mut head, &mut previous);
+            position += 1;
+        }
+    }
+
+    // End of block.
+    write_literal(&mut writer, 256);
+    writer.flush();
+
+    let checksum = adler32(data);
+    writer.bytes.push((checksum >> 24) as u8);
+    writer.bytes.push((checksum >> 16) as u8);
+    writer.bytes.push((checksum >> 8) as u8);
+    writer.bytes.push(checksum as u8);
+
+    writer.bytes
+}
+
+pub fn adler32(data: &[u8]) -> u32 {
+    let mut a: u32 = 1;
+    let mut b: u32 = 0;
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= 65521;
+        b %= 65521;
+    }
+    (b << 16) | a
+}
+
+fn hash(data: &[u8], position: usize) -> usize {
+    let value = ((data[position] as usize) << 16) | ((data[position + 1] as usize) << 8) | (data[position + 2] as usize);
+    (value.wrapping_mul(2654435761) >> 8) & (HASH_SIZE - 1)
+}
+
+fn insert_hash(data: &[u8], position: usize, head: &mut [usize], previous: &mut [usize]) {
+    if position + MIN_MATCH > data.len() {
+        return;
+    }
+    let h = hash(data, position);
+    previous[position % WINDOW_SIZE] = head[h];
+    head[h] = position;
+}
+
+fn find_match(data: &[u8], position: usize, head: &[usize], previous: &[usize]) -> (usize, usize) {
+    if position + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+
+    let max_length = MAX_MATCH.min(data.len() - position);
+    let mut best_length = 0;
+    let mut best_distance = 0;
+
+    let mut candidate = head[hash(data, position)];
+    let mut chain = 0;
+    while candidate != NO_POSITION && chain < MAX_CHAIN {
+        let distance = position - candidate;
+        if distance > WINDOW_SIZE - 1 {
+            break;
+        }
+
+        let mut length = 0;
+        while length < max_length && data[candidate + length] == data[position + length] {
+            length += 1;
+        }
+        if length > best_length {
+            best_length = length;
+            best_distance = distance;
+            if length == max_length {
+                break;
+            }
+        }
+
+        let next = previous[candidate % WINDOW_SIZE];
+        if next == NO_POSITION || next >= candidate {
+            break;
+        }
+        candidate = next;
+        chain += 1;
+    }
+
+    (best_length, best_distance)
+}
+
+fn write_literal(writer: &mut BitWriter, value: u16) {
+    let (code, length) = match value {
+        0..=143 => (0x30 + value, 8),
+        144..=255 => (0x190 + value - 144, 9),
+        256..=279 => (value - 256, 7),
+        _ => (0xC0 + value - 280, 8)
+    };
+    writer.write_huffman(code as u32, length);
+}
+
+fn write_length(writer: &mut BitWriter, length: usize) {
+    let mut index = LENGTH_BASE.len() - 1;
+    while LENGTH_BASE[index] as usize > length {
+        index -= 1;
+    }
+    write_literal(writer, 257 + index as u16);
+    writer.write_bits((length - LENGTH_BASE[index] as usize) as u32, LENGTH_EXTRA_BITS[index] as u32);
+}
+
+fn write_distance(writer: &mut BitWriter, distance: usize) {
+    let mut index = DISTANCE_BASE.len() - 1;
+    while DISTANCE_BASE[index] as usize > distance {
+        index -= 1;
+    }
+    writer.write_huffman(index as u32, 5);
+    writer.write_bits((distance - DISTANCE_BASE[index] as usize) as u32, DISTANCE_EXTRA_BITS[index] as u32);
+}
+
+struct BitWriter {
+    bytes: Vec<u8>,
+    buffer: u32,
+    count: u32
+}
+
+impl BitWriter {
+    fn new() -> BitWriter {
+        BitWriter {
+            bytes: Vec::new(),
+            buffer: 0,
+            count: 0
+        }
+    }
+
+    // Writes `length` bits of `value`, least significant bit first.
+    fn write_bits(&mut self, value: u32, length: u32) {
+        self.buffer |= value << self.count;
+        self.count += length;
+        while self.count >= 8 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    // Huffman codes are packed starting with their most significant bit.
+    fn write_huffman(&mut self, code: u32, length: u32) {
+        let mut reversed = 0;
+        for bit in 0..length {
+            reversed |= ((code >> bit) & 1) << (length - 1 - bit);
+        }
+        self.write_bits(reversed, length);
+    }
+
+    fn flush(&mut self) {
+        if self.count > 0 {
+            self.bytes.push(self.buffer as u8);
+            self.buffer = 0;
+            self.count = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress, adler32, LENGTH_BASE, LENGTH_EXTRA_BITS, DISTANCE_BASE, DISTANCE_EXTRA_BITS};
+
+    struct BitReader<'a> {
+        bytes: &'a [u8],
+        position: usize,
+        bit: u32
+    }
+
+    impl<'a> BitReader<'a> {
+        fn read_bits(&mut self, length: u32) -> u32 {
+            let mut value = 0;
+            for index in 0..length {
+                let bit = (self.bytes[self.position] >> self.bit) & 1;
+                value |= (bit as u32) << index;
+                self.bit += 1;
+                if self.bit == 8 {
+                    self.bit = 0;
+                    self.position += 1;
+                }
+            }
+            value
+        }
+
+        fn read_huffman_bit(&mut self, code: u32) -> u32 {
+            (code << 1) | self.read_bits(1)
+        }
+
+        fn align(&mut self) {
+            if self.bit > 0 {
+                self.bit = 0;
+                self.position += 1;
+            }
+        }
+
+        // Decodes a symbol of the fixed literal/length code.
+        fn read_fixed_literal(&mut self) -> u32 {
+            let mut code = 0;
+            for _ in 0..7 {
+                code = self.read_huffman_bit(code);
+            }
+            if code <= 0x17 {
+                return code + 256;
+            }
+            code = self.read_huffman_bit(code);
+            match code {
+                0x30..=0xBF => code - 0x30,
+                0xC0..=0xC7 => code - 0xC0 + 280,
+                _ => self.read_huffman_bit(code) - 0x190 + 144
+            }
+        }
+
+        fn read_fixed_distance(&mut self) -> u32 {
+            let mut code = 0;
+            for _ in 0..5 {
+                code = self.read_huffman_bit(code);
+            }
+            code
+        }
+    }
+
+    // A minimal inflate for the stored and fixed Huffman blocks, enough to
+    // check what `compress` writes.
+    fn decompress(data: &[u8]) -> Vec<u8> {
+        assert_eq!(((data[0] as u32) << 8 | data[1] as u32) % 31, 0, "bad zlib header check");
+        let mut reader = BitReader { bytes: &data[2..], position: 0, bit: 0 };
+        let mut output = Vec::new();
+
+        loop {
+            let is_final = reader.read_bits(1) == 1;
+            match reader.read_bits(2) {
+                0 => {
+                    reader.align();
+                    let length = reader.read_bits(16) as usize;
+                    assert_eq!(reader.read_bits(16) as usize, !length & 0xFFFF);
+                    for _ in 0..length {
+                        output.push(reader.read_bits(8) as u8);
+                    }
+                },
+                1 => loop {
+                    let symbol = reader.read_fixed_literal() as usize;
+                    if symbol < 256 {
+                        output.push(symbol as u8);
+                        continue;
+                    } else if symbol == 256 {
+                        break;
+                    }
+                    let index = symbol - 257;
+                    let length = LENGTH_BASE[index] as usize + reader.read_bits(LENGTH_EXTRA_BITS[index] as u32) as usize;
+                    let index = reader.read_fixed_distance() as usize;
+                    let distance = DISTANCE_BASE[index] as usize + reader.read_bits(DISTANCE_EXTRA_BITS[index] as u32) as usize;
+                    assert!(distance <= output.len(), "distance {} reaches before the start", distance);
+                    for _ in 0..length {
+                        let byte = output[output.len() - distance];
+                        output.push(byte);
+                    }
+                },
+                block_type => panic!("unexpected block type {}", block_type)
+            }
+            if is_final {
+                break;
+            }
+        }
+
+        reader.align();
+        let checksum = reader.read_bits(32).swap_bytes();
+        assert_eq!(checksum, adler32(&output), "checksum does not match the data");
+        output
+    }
+
+    fn assert_round_trip(data: &[u8]) {
+        assert!(decompress(&compress(data)) == data, "round trip of {} bytes changed the data", data.len());
+    }
+
+    #[test]
+    fn computes_adler32_reference_values() {
+        assert_eq!(adler32(b""), 1);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E60398);
+    }
+
+    #[test]
+    fn decodes_a_stored_block() {
+        // "abc" in a single stored block.
+        let data = [0x78, 0x01, 0x01, 0x03, 0x00, 0xFC, 0xFF, 0x61, 0x62, 0x63, 0x02, 0x4D, 0x01, 0x27];
+        assert_eq!(decompress(&data), b"abc");
+    }
+
+    #[test]
+    fn round_trips_literals() {
+        assert_round_trip(b"");
+        assert_round_trip(b"Wikipedia");
+        let all_bytes: Vec<u8> = (0..=255).collect();
+        assert_round_trip(&all_bytes);
+    }
+
+    #[test]
+    fn round_trips_repeated_data() {
+        // Long runs use the longest matches, and overlapping ones.
+        assert_round_trip(&[7; 1000]);
+        assert_round_trip(&b"abc".repeat(1000));
+    }
+
+    #[test]
+    fn round_trips_data_longer_than_the_window() {
+        let mut state: u32 = 1;
+        let noise: Vec<u8> = (0..40000).map(|_| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            (state >> 16) as u8
+        }).collect();
+        let mut data = noise.clone();
+        data.extend_from_slice(&noise[10000..]);
+        assert_round_trip(&data);
+    }
+}
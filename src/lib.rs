@@ -0,0 +1,13 @@
+extern crate num_cpus;
+extern crate time;
+
+pub mod color;
+pub mod geometry;
+pub mod image;
+pub mod material;
+pub mod mesh;
+pub mod render;
+pub mod scene;
+pub mod scene_file;
+pub mod table;
+pub mod texture;
@@ -0,0 +1,38 @@
+use color::{ColorRGB};
+use geometry::{Direction3D};
+use scene::{Hit};
+
+use super::{Material, ScatteredRay};
+
+// A surface that only gives off light of its color, like that of a light
+// source. It is not lit by other lights and does not reflect anything.
+#[derive(Clone, Debug)]
+pub struct Emission {
+    color: ColorRGB
+}
+
+impl Emission {
+    pub fn new(color: &ColorRGB) -> Emission {
+        Emission {
+            color: color.clone()
+        }
+    }
+
+    pub fn get_color(&self) -> &ColorRGB {
+        &self.color
+    }
+}
+
+impl Material for Emission {
+    fn get_emission(&self, _hit: &Hit) -> ColorRGB {
+        self.color.clone()
+    }
+
+    fn evaluate(&self, _hit: &Hit, _to_viewer: &Direction3D, _to_light: &Direction3D, _light_color: &ColorRGB) -> ColorRGB {
+        ColorRGB::black().clone()
+    }
+
+    fn sample(&self, _hit: &Hit, _direction: &Direction3D, _outside_index: f32) -> Vec<ScatteredRay> {
+        Vec::new()
+    }
+}
@@ -0,0 +1,35 @@
+// How the light that reaches a transparent surface is divided between the
+// reflected and the refracted rays.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Fresnel {
+    // Refraction gets all of it, and `reflection` adds a fixed amount.
+    None,
+    // The Fresnel equations for unpolarized light.
+    Exact,
+    // Schlick's approximation of the Fresnel equations.
+    Schlick
+}
+
+impl Fresnel {
+    // The fraction of the light that is reflected. `n` is the refractive index
+    // on the side the ray comes from divided by the one on the other side,
+    // and the cosines are those of the angles of incidence and refraction.
+    pub fn get_reflectance(&self, n: f32, cos_i: f32, cos_t: f32) -> f32 {
+        match *self {
+            Fresnel::None => 0.0,
+            Fresnel::Exact => {
+                let perpendicular = (n * cos_i - cos_t) / (n * cos_i + cos_t);
+                let parallel = (cos_i - n * cos_t) / (cos_i + n * cos_t);
+                0.5 * (perpendicular * perpendicular + parallel * parallel)
+            },
+            Fresnel::Schlick => {
+                let r0 = ((n - 1.0) / (n + 1.0)).powi(2);
+                // The angle on the side of the less dense medium must be used,
+                // which is the refracted one when the ray leaves the denser
+                // medium.
+                let cos = if n > 1.0 { cos_t } else { cos_i };
+                r0 + (1.0 - r0) * (1.0 - cos).powi(5)
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+use std::f32::consts::{PI};
+
+use color::{ColorRGB};
+use geometry::{Vector3D, Direction3D};
+
+// Reflectance of dielectrics, like plastic or stone, at normal incidence.
+const DIELECTRIC_REFLECTANCE: f32 = 0.04;
+
+// Smallest squared roughness, which keeps the distribution finite on
+// perfectly smooth surfaces.
+const MIN_ALPHA: f32 = 1.0e-3;
+
+// The metallic/roughness model: a Lambertian base under a GGX microfacet
+// specular layer with Smith masking and Schlick's Fresnel term. Metals have
+// no diffuse part and tint their specular reflection with the base color.
+pub struct Microfacet {
+    base_color: ColorRGB,
+    metallic: f32,
+    alpha: f32,
+    specular_color: ColorRGB
+}
+
+impl Microfacet {
+    pub fn new(base_color: &ColorRGB, metallic: f32, roughness: f32) -> Microfacet {
+        let metallic = metallic.clamp(0.0, 1.0);
+        let roughness = roughness.clamp(0.0, 1.0);
+        let dielectric = ColorRGB::from_rgb(DIELECTRIC_REFLECTANCE, DIELECTRIC_REFLECTANCE, DIELECTRIC_REFLECTANCE);
+        Microfacet {
+            base_color: base_color.clone(),
+            metallic: metallic,
+            alpha: (roughness * roughness).max(MIN_ALPHA),
+            specular_color: dielectric * (1.0 - metallic) + base_color * metallic
+        }
+    }
+
+    // The light reflected towards the viewer from a light of the given color,
+    // including the cosine of the angle to the light. It is scaled by pi so
+    // that a white diffuse surface facing a light has the color of the light,
+    // as it does with the other model.
+    pub fn shade(&self, normal: &Direction3D, to_viewer: &Direction3D, to_light: &Direction3D, light_color: &ColorRGB) -> ColorRGB {
+        let n_dot_l = Vector3D::dot(normal, to_light);
+        let n_dot_v = Vector3D::dot(normal, to_viewer);
+        if n_dot_l <= 0.0 || n_dot_v <= 0.0 {
+            return ColorRGB::black().clone();
+        }
+
+        let halfway = (to_viewer + to_light).to_unit();
+        let n_dot_h = Vector3D::dot(normal, &halfway).max(0.0);
+        let v_dot_h = Vector3D::dot(to_viewer, &halfway).max(0.0);
+
+        let fresnel = schlick(&self.specular_color, v_dot_h);
+        let specular = &fresnel * (self.distribution(n_dot_h) * self.masking(n_dot_l) * self.masking(n_dot_v) / (4.0 * n_dot_l * n_dot_v));
+        let diffuse_weight = ColorRGB::from_rgb(1.0 - fresnel.red, 1.0 - fresnel.green, 1.0 - fresnel.blue) * (1.0 - self.metallic);
+        let diffuse = &self.base_color * &diffuse_weight * (1.0 / PI);
+
+        (diffuse + specular) * light_color * (PI * n_dot_l)
+    }
+
+    // The weight of the mirror reflection of the scene. A single reflected
+    // ray cannot blur the reflection, so it fades out on rough surfaces
+    // instead.
+    pub fn get_reflectance(&self, normal: &Direction3D, to_viewer: &Direction3D) -> ColorRGB {
+        let n_dot_v = Vector3D::dot(normal, to_viewer).max(0.0);
+        let smoothness = 1.0 - self.alpha.sqrt();
+        schlick(&self.specular_color, n_dot_v) * (smoothness * smoothness)
+    }
+
+    // GGX (Trowbridge-Reitz) distribution of microfacet normals.
+    fn distribution(&self, n_dot_h: f32) -> f32 {
+        let alpha_squared = self.alpha * self.alpha;
+        let denominator = n_dot_h * n_dot_h * (alpha_squared - 1.0) + 1.0;
+        alpha_squared / (PI * denominator * denominator)
+    }
+
+    // Smith's masking function for GGX, for one direction.
+    fn masking(&self, n_dot_x: f32) -> f32 {
+        let alpha_squared = self.alpha * self.alpha;
+        2.0 * n_dot_x / (n_dot_x + (alpha_squared + (1.0 - alpha_squared) * n_dot_x * n_dot_x).sqrt())
+    }
+}
+
+fn schlick(specular_color: &ColorRGB, cos: f32) -> ColorRGB {
+    let weight = (1.0 - cos).max(0.0).powi(5);
+    specular_color * (1.0 - weight) + ColorRGB::white() * weight
+}
@@ -0,0 +1,56 @@
+use std::fmt::{Debug};
+
+use color::{ColorRGB};
+use geometry::{Direction3D};
+use scene::{Hit};
+
+pub use self::fresnel::{Fresnel};
+pub use self::standard::{StandardMaterial, MaterialBuilder, Model};
+pub use self::emission::{Emission};
+
+mod fresnel;
+mod standard;
+mod emission;
+mod microfacet;
+
+// Decides how a surface looks. Objects hand the material of the surface to the
+// tracer with each hit, and the tracer asks it what the surface gives off, how
+// it scatters the light of each light source towards the viewer, and which
+// rays to follow from it.
+pub trait Material: Debug + Sync + Send {
+    // Tilts the shading normal of the hit, for materials with normal or bump
+    // maps. It is called before anything else is asked of the material.
+    fn apply_normal_maps(&self, _hit: &mut Hit) {
+    }
+
+    // The light that the surface gives off by itself.
+    fn get_emission(&self, _hit: &Hit) -> ColorRGB {
+        ColorRGB::black().clone()
+    }
+
+    // The part of the light of `light_color`, arriving from `to_light`, that
+    // the surface sends towards `to_viewer`.
+    fn evaluate(&self, hit: &Hit, to_viewer: &Direction3D, to_light: &Direction3D, light_color: &ColorRGB) -> ColorRGB;
+
+    // The rays to follow from the hit of a ray travelling in `direction`.
+    // `outside_index` is the refractive index around the object.
+    fn sample(&self, hit: &Hit, direction: &Direction3D, outside_index: f32) -> Vec<ScatteredRay>;
+
+    // The part of the light that is left after passing `distance` through
+    // the material, for rays that go through the surface.
+    fn get_transparency(&self, _hit: &Hit, _distance: f32) -> ColorRGB {
+        ColorRGB::white().clone()
+    }
+}
+
+// A ray to follow from a surface, and how much of the light it brings back is
+// passed on.
+#[derive(Clone, Debug)]
+pub struct ScatteredRay {
+    pub direction: Direction3D,
+    pub weight: ColorRGB,
+
+    // Rays that go through the surface are also weighed by the transparency
+    // of the material over the distance they travel.
+    pub is_transmitted: bool
+}
@@ -0,0 +1,439 @@
+use std::sync::{Arc};
+
+use color::ColorRGB;
+use geometry::{Point3D, Vector3D, Direction3D};
+use scene::{Hit};
+use texture::{Texture};
+
+use super::{Material, ScatteredRay, Fresnel};
+use super::microfacet::{Microfacet};
+
+// How a surface reflects the light that reaches it directly.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Model {
+    // Lambertian `diffuse` and Phong `specular` lighting, and a fixed amount
+    // of mirror `reflection`.
+    Phong,
+    // The physically based metallic/roughness model, with `color` as the base
+    // color. Reflection of the scene follows from the model, so `diffuse`,
+    // `specular`, `shininess` and `reflection` are not used.
+    MetallicRoughness
+}
+
+// The material of scene files and meshes, lit with one of the models above,
+// with optional reflection and refraction, and textures for its properties.
+#[derive(Clone, Debug)]
+pub struct StandardMaterial {
+    pub color: ColorRGB,
+    pub diffuse: f32,
+    pub specular: f32,
+    pub shininess: u32,
+    pub reflection:f32,
+    pub refraction: f32,
+    pub refractive_index: f32,
+    pub fresnel: Fresnel,
+    pub model: Model,
+    pub metallic: f32,
+    pub roughness: f32,
+
+    // Optional textures that vary the properties above over the surface. The
+    // color map tints `color`, and the others scale their coefficient by the
+    // average of the channels of the texture.
+    pub color_map: Option<Arc<Texture>>,
+    pub diffuse_map: Option<Arc<Texture>>,
+    pub specular_map: Option<Arc<Texture>>,
+    pub reflection_map: Option<Arc<Texture>>,
+    pub metallic_map: Option<Arc<Texture>>,
+    pub roughness_map: Option<Arc<Texture>>,
+
+    // Optional textures that tilt the shading normal: a tangent space normal
+    // map, and a height map whose slope is multiplied by `bump_scale`.
+    pub normal_map: Option<Arc<Texture>>,
+    pub bump_map: Option<Arc<Texture>>,
+    pub bump_scale: f32
+}
+
+impl StandardMaterial {
+    pub fn new(color: &ColorRGB) -> StandardMaterial {
+        StandardMaterial {
+            color: color.clone(),
+            diffuse: 1.0,
+            specular: 0.0,
+            shininess: 0,
+            reflection: 0.0,
+            refraction: 0.0,
+            refractive_index: 0.0,
+            fresnel: Fresnel::None,
+            model: Model::Phong,
+            metallic: 0.0,
+            roughness: 0.5,
+            color_map: None,
+            diffuse_map: None,
+            specular_map: None,
+            reflection_map: None,
+            metallic_map: None,
+            roughness_map: None,
+            normal_map: None,
+            bump_map: None,
+            bump_scale: 1.0
+        }
+    }
+
+    pub fn get_color(&self, uv: (f32, f32), point: &Point3D) -> ColorRGB {
+        self.apply_color_map(&self.color, uv, point)
+    }
+
+    // Tints a color that replaces `color`, like a vertex color, with the color
+    // map.
+    pub fn apply_color_map(&self, color: &ColorRGB, uv: (f32, f32), point: &Point3D) -> ColorRGB {
+        match self.color_map {
+            Some(ref texture) => color * &texture.get_color(uv, point),
+            None => color.clone()
+        }
+    }
+
+    pub fn get_diffuse(&self, uv: (f32, f32), point: &Point3D) -> f32 {
+        apply_map(self.diffuse, &self.diffuse_map, uv, point)
+    }
+
+    pub fn get_specular(&self, uv: (f32, f32), point: &Point3D) -> f32 {
+        apply_map(self.specular, &self.specular_map, uv, point)
+    }
+
+    pub fn get_reflection(&self, uv: (f32, f32), point: &Point3D) -> f32 {
+        apply_map(self.reflection, &self.reflection_map, uv, point)
+    }
+
+    pub fn get_metallic(&self, uv: (f32, f32), point: &Point3D) -> f32 {
+        apply_map(self.metallic, &self.metallic_map, uv, point)
+    }
+
+    pub fn get_roughness(&self, uv: (f32, f32), point: &Point3D) -> f32 {
+        apply_map(self.roughness, &self.roughness_map, uv, point)
+    }
+
+    // The color at the hit, which is the vertex color instead of `color` on
+    // meshes that have them.
+    fn get_hit_color(&self, hit: &Hit) -> ColorRGB {
+        match hit.vertex_color {
+            Some(ref color) => self.apply_color_map(color, hit.uv, &hit.point),
+            None => self.get_color(hit.uv, &hit.point)
+        }
+    }
+
+    fn get_microfacet(&self, hit: &Hit, color: &ColorRGB) -> Option<Microfacet> {
+        match self.model {
+            Model::Phong => None,
+            Model::MetallicRoughness => Some(Microfacet::new(color, self.get_metallic(hit.uv, &hit.point), self.get_roughness(hit.uv, &hit.point)))
+        }
+    }
+
+    // Finds where a ray travelling in `direction` goes through the surface,
+    // and how much of the light that would pass through it is reflected
+    // instead.
+    fn refract(&self, hit: &Hit, direction: &Direction3D, outside_index: f32) -> (Option<Direction3D>, f32) {
+        let normal = &hit.shading_normal;
+        let n;
+        let facing_normal;
+        if !hit.front_face {
+            // Internal refraction
+            n = self.refractive_index / outside_index;
+            facing_normal = -normal;
+        } else {
+            // External refraction
+            n = outside_index / self.refractive_index;
+            facing_normal = normal.clone();
+        }
+        let cos_i = -Vector3D::dot(direction, &facing_normal);
+
+        let cos2_t = 1.0 - n * n * (1.0 - cos_i * cos_i);
+        if cos2_t > 0.0 {
+            let cos_t = cos2_t.sqrt();
+            let refracted_direction = (direction * n + &facing_normal * (n * cos_i - cos_t)).to_unit();
+            (Some(refracted_direction), self.fresnel.get_reflectance(n, cos_i, cos_t))
+        } else if self.fresnel != Fresnel::None {
+            // Total internal reflection
+            (None, 1.0)
+        } else {
+            (None, 0.0)
+        }
+    }
+}
+
+impl Material for StandardMaterial {
+    fn apply_normal_maps(&self, hit: &mut Hit) {
+        if let Some(ref texture) = self.normal_map {
+            hit.apply_normal_map(&**texture);
+        }
+        if let Some(ref texture) = self.bump_map {
+            hit.apply_bump_map(&**texture, self.bump_scale);
+        }
+    }
+
+    fn evaluate(&self, hit: &Hit, to_viewer: &Direction3D, to_light: &Direction3D, light_color: &ColorRGB) -> ColorRGB {
+        let normal = &hit.shading_normal;
+        let color = self.get_hit_color(hit);
+        if let Some(microfacet) = self.get_microfacet(hit, &color) {
+            return microfacet.shade(normal, to_viewer, to_light, light_color);
+        }
+
+        let mut total_color = ColorRGB::black().clone();
+
+        // Calculate the diffusive lighting from the light.
+        let diffuse = self.get_diffuse(hit.uv, &hit.point);
+        if diffuse > 0.0 {
+            let percentage_of_light = Vector3D::dot(normal, to_light);
+            if percentage_of_light > 0.0 {
+                total_color = &total_color + (light_color * &color) * (diffuse * percentage_of_light);
+            }
+        }
+
+        // Calculate the specular lighting from the light.
+        let specular = self.get_specular(hit.uv, &hit.point);
+        if specular > 0.0 && self.shininess > 0 {
+            let reflected_direction = (to_light - normal * 2.0 * Vector3D::dot(to_light, normal)).to_unit();
+            let percentage_of_light = -Vector3D::dot(to_viewer, &reflected_direction);
+            if percentage_of_light > 0.0 {
+                total_color = &total_color + light_color * (specular * percentage_of_light.powi(self.shininess as i32));
+            }
+        }
+
+        total_color
+    }
+
+    // Mirror reflection is tinted by the surface, while reflection off a
+    // transparent surface is not.
+    fn sample(&self, hit: &Hit, direction: &Direction3D, outside_index: f32) -> Vec<ScatteredRay> {
+        let normal = &hit.shading_normal;
+        let color = self.get_hit_color(hit);
+        let mut rays = Vec::new();
+
+        let (refracted_direction, fresnel_reflectance) = if self.refraction > 0.0 {
+            self.refract(hit, direction, outside_index)
+        } else {
+            (None, 0.0)
+        };
+
+        let surface_reflection = match self.get_microfacet(hit, &color) {
+            Some(microfacet) => microfacet.get_reflectance(normal, &-direction),
+            None => &color * self.get_reflection(hit.uv, &hit.point)
+        };
+        let reflected_weight = surface_reflection + ColorRGB::white() * fresnel_reflectance;
+        if reflected_weight.red > 0.0 || reflected_weight.green > 0.0 || reflected_weight.blue > 0.0 {
+            rays.push(ScatteredRay {
+                direction: (direction - normal * 2.0 * Vector3D::dot(direction, normal)).to_unit(),
+                weight: reflected_weight,
+                is_transmitted: false
+            });
+        }
+
+        if let Some(refracted_direction) = refracted_direction {
+            rays.push(ScatteredRay {
+                direction: refracted_direction,
+                weight: ColorRGB::white() * (1.0 - fresnel_reflectance),
+                is_transmitted: true
+            });
+        }
+
+        rays
+    }
+
+    // Beer's Law
+    fn get_transparency(&self, hit: &Hit, distance: f32) -> ColorRGB {
+        let absorbance = self.get_hit_color(hit) * (0.15 * -distance);
+        ColorRGB::from_rgb(absorbance.red.exp(), absorbance.green.exp(), absorbance.blue.exp())
+    }
+}
+
+fn apply_map(value: f32, map: &Option<Arc<Texture>>, uv: (f32, f32), point: &Point3D) -> f32 {
+    match *map {
+        Some(ref texture) => {
+            let color = texture.get_color(uv, point);
+            value * (color.red + color.green + color.blue) / 3.0
+        },
+        None => value
+    }
+}
+
+#[derive(Debug)]
+pub struct MaterialBuilder {
+    color: ColorRGB,
+    diffuse: f32,
+    specular: f32,
+    shininess: u32,
+    reflection: f32,
+    refraction: f32,
+    refractive_index: f32,
+    fresnel: Fresnel,
+    model: Model,
+    metallic: f32,
+    roughness: f32,
+    color_map: Option<Arc<Texture>>,
+    diffuse_map: Option<Arc<Texture>>,
+    specular_map: Option<Arc<Texture>>,
+    reflection_map: Option<Arc<Texture>>,
+    metallic_map: Option<Arc<Texture>>,
+    roughness_map: Option<Arc<Texture>>,
+    normal_map: Option<Arc<Texture>>,
+    bump_map: Option<Arc<Texture>>,
+    bump_scale: f32
+}
+
+impl MaterialBuilder {
+
+    pub fn new() -> MaterialBuilder {
+        MaterialBuilder {
+            color: ColorRGB::white().clone(),
+            diffuse: 1.0,
+            specular: 0.0,
+            shininess: 0,
+            reflection: 0.0,
+            refraction: 0.0,
+            refractive_index: 0.0,
+            fresnel: Fresnel::None,
+            model: Model::Phong,
+            metallic: 0.0,
+            roughness: 0.5,
+            color_map: None,
+            diffuse_map: None,
+            specular_map: None,
+            reflection_map: None,
+            metallic_map: None,
+            roughness_map: None,
+            normal_map: None,
+            bump_map: None,
+            bump_scale: 1.0
+        }
+    }
+
+    pub fn color(&mut self, color: &ColorRGB) -> &mut MaterialBuilder {
+        self.color = color.clone();
+        self
+    }
+
+    pub fn diffuse(&mut self, diffuse: f32) -> &mut MaterialBuilder {
+        self.diffuse = diffuse;
+        self
+    }
+
+    pub fn specular(&mut self, specular: f32) -> &mut MaterialBuilder {
+        self.specular = specular;
+        self
+    }
+
+    pub fn shininess(&mut self, shininess: u32) -> &mut MaterialBuilder {
+        self.shininess = shininess;
+        self
+    }
+    
+    pub fn reflection(&mut self, reflection: f32) -> &mut MaterialBuilder {
+        self.reflection = reflection;
+        self
+    }
+    
+    pub fn refraction(&mut self, refraction: f32) -> &mut MaterialBuilder {
+        self.refraction = refraction;
+        self
+    }
+    
+    pub fn refractive_index(&mut self, refractive_index: f32) -> &mut MaterialBuilder {
+        self.refractive_index = refractive_index;
+        self
+    }
+    
+    pub fn fresnel(&mut self, fresnel: Fresnel) -> &mut MaterialBuilder {
+        self.fresnel = fresnel;
+        self
+    }
+
+    pub fn model(&mut self, model: Model) -> &mut MaterialBuilder {
+        self.model = model;
+        self
+    }
+
+    pub fn metallic(&mut self, metallic: f32) -> &mut MaterialBuilder {
+        self.metallic = metallic;
+        self
+    }
+
+    pub fn roughness(&mut self, roughness: f32) -> &mut MaterialBuilder {
+        self.roughness = roughness;
+        self
+    }
+
+    pub fn color_map(&mut self, texture: &Arc<Texture>) -> &mut MaterialBuilder {
+        self.color_map = Some(texture.clone());
+        self
+    }
+
+    pub fn diffuse_map(&mut self, texture: &Arc<Texture>) -> &mut MaterialBuilder {
+        self.diffuse_map = Some(texture.clone());
+        self
+    }
+
+    pub fn specular_map(&mut self, texture: &Arc<Texture>) -> &mut MaterialBuilder {
+        self.specular_map = Some(texture.clone());
+        self
+    }
+
+    pub fn reflection_map(&mut self, texture: &Arc<Texture>) -> &mut MaterialBuilder {
+        self.reflection_map = Some(texture.clone());
+        self
+    }
+
+    pub fn metallic_map(&mut self, texture: &Arc<Texture>) -> &mut MaterialBuilder {
+        self.metallic_map = Some(texture.clone());
+        self
+    }
+
+    pub fn roughness_map(&mut self, texture: &Arc<Texture>) -> &mut MaterialBuilder {
+        self.roughness_map = Some(texture.clone());
+        self
+    }
+
+    pub fn normal_map(&mut self, texture: &Arc<Texture>) -> &mut MaterialBuilder {
+        self.normal_map = Some(texture.clone());
+        self
+    }
+
+    pub fn bump_map(&mut self, texture: &Arc<Texture>) -> &mut MaterialBuilder {
+        self.bump_map = Some(texture.clone());
+        self
+    }
+
+    pub fn bump_scale(&mut self, bump_scale: f32) -> &mut MaterialBuilder {
+        self.bump_scale = bump_scale;
+        self
+    }
+
+    pub fn to_material(&self) -> Arc<Material> {
+        Arc::new(StandardMaterial {
+            color: self.color.clone(), 
+            diffuse: self.diffuse, 
+            specular: self.specular, 
+            shininess: self.shininess, 
+            reflection: self.reflection, 
+            refraction: self.refraction, 
+            refractive_index: self.refractive_index,
+            fresnel: self.fresnel,
+            model: self.model,
+            metallic: self.metallic,
+            roughness: self.roughness,
+            color_map: self.color_map.clone(),
+            diffuse_map: self.diffuse_map.clone(),
+            specular_map: self.specular_map.clone(),
+            reflection_map: self.reflection_map.clone(),
+            metallic_map: self.metallic_map.clone(),
+            roughness_map: self.roughness_map.clone(),
+            normal_map: self.normal_map.clone(),
+            bump_map: self.bump_map.clone(),
+            bump_scale: self.bump_scale
+        })
+    }
+}
+
+impl Default for MaterialBuilder {
+    fn default() -> MaterialBuilder {
+        MaterialBuilder::new()
+    }
+}
@@ -0,0 +1,134 @@
+use std::error::{Error};
+use std::fmt;
+use std::io;
+use std::path::{Path};
+use std::sync::{Arc};
+
+use color::{ColorRGB};
+use geometry::{Point3D, Direction3D, Aabb};
+use material::{Material};
+
+pub use self::obj::{ObjFile};
+pub use self::ply::{PlyFile};
+
+mod obj;
+mod ply;
+
+// A triangle mesh as read from a model file. Faces refer to the attribute
+// lists by index, so shared vertices are stored once. `colors` is either empty
+// or holds one color per position.
+#[derive(Clone, Debug)]
+pub struct Mesh {
+    pub positions: Vec<Point3D>,
+    pub normals: Vec<Direction3D>,
+    pub uvs: Vec<(f32, f32)>,
+    pub colors: Vec<ColorRGB>,
+    pub faces: Vec<MeshFace>,
+    pub materials: Vec<Arc<Material>>,
+    pub groups: Vec<String>
+}
+
+#[derive(Clone, Debug)]
+pub struct MeshFace {
+    pub positions: [usize; 3],
+    pub normals: Option<[usize; 3]>,
+    pub uvs: Option<[usize; 3]>,
+    pub material: Option<usize>,
+    pub group: Option<usize>
+}
+
+impl Mesh {
+    pub fn new() -> Mesh {
+        Mesh {
+            positions: Vec::new(),
+            normals: Vec::new(),
+            uvs: Vec::new(),
+            colors: Vec::new(),
+            faces: Vec::new(),
+            materials: Vec::new(),
+            groups: Vec::new()
+        }
+    }
+
+    // Replaces every material in the mesh with `material`.
+    pub fn set_material(&mut self, material: &Arc<Material>) {
+        self.materials = vec![material.clone()];
+        for face in &mut self.faces {
+            face.material = Some(0);
+        }
+    }
+
+    pub fn get_bounds(&self) -> Aabb {
+        Aabb::from_points(&self.positions)
+    }
+}
+
+impl Default for Mesh {
+    fn default() -> Mesh {
+        Mesh::new()
+    }
+}
+
+// Loads a mesh, choosing the format from the file extension.
+pub fn load_mesh(file_name: &str) -> Result<Mesh, MeshError> {
+    let extension = Path::new(file_name).extension()
+        .and_then(|extension| extension.to_str())
+        .map(|extension| extension.to_lowercase());
+
+    match extension.as_deref() {
+        Some("obj") => ObjFile::new(file_name).load(),
+        Some("ply") => PlyFile::new(file_name).load(),
+        _ => Err(MeshError::Io(io::Error::new(io::ErrorKind::InvalidInput, format!("unsupported mesh format: {}", file_name))))
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub enum MeshError {
+    Io(io::Error),
+    Parse {
+        file_name: String,
+        line: usize,
+        message: String
+    },
+    Format {
+        file_name: String,
+        message: String
+    }
+}
+
+impl MeshError {
+    fn parse(file_name: &str, line: usize, message: String) -> MeshError {
+        MeshError::Parse {
+            file_name: file_name.to_string(),
+            line: line,
+            message: message
+        }
+    }
+
+    fn format(file_name: &str, message: String) -> MeshError {
+        MeshError::Format {
+            file_name: file_name.to_string(),
+            message: message
+        }
+    }
+}
+
+impl fmt::Display for MeshError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MeshError::Io(ref error) => write!(f, "{}", error),
+            MeshError::Parse {ref file_name, line, ref message} => write!(f, "{}:{}: {}", file_name, line, message),
+            MeshError::Format {ref file_name, ref message} => write!(f, "{}: {}", file_name, message)
+        }
+    }
+}
+
+impl Error for MeshError {}
+
+impl From<io::Error> for MeshError {
+    fn from(error: io::Error) -> MeshError {
+        MeshError::Io(error)
+    }
+}
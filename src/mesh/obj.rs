@@ -0,0 +1,334 @@
+use std::collections::{HashMap};
+use std::fs::{File};
+use std::io::{Read};
+use std::path::{Path, PathBuf};
+use std::str::{FromStr};
+
+use color::{ColorRGB};
+use geometry::{Point3D, Direction3D};
+use material::{MaterialBuilder};
+
+use super::{Mesh, MeshFace, MeshError};
+
+// Wavefront OBJ reader. Supports positions, texture coordinates, normals,
+// polygonal faces (triangulated as fans, so they should be convex), groups,
+// objects and MTL material libraries. Other statements are ignored.
+#[derive(Debug)]
+pub struct ObjFile {
+    file_name: String
+}
+
+struct ObjParser<'a> {
+    file_name: &'a str,
+    directory: PathBuf,
+    mesh: Mesh,
+    material_indices: HashMap<String, usize>,
+    current_material: Option<usize>,
+    current_group: Option<usize>
+}
+
+// One `v/vt/vn` corner of a face, as indices into the mesh attributes.
+struct FaceCorner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>
+}
+
+impl ObjFile {
+    pub fn new(file_name: &str) -> ObjFile {
+        ObjFile {
+            file_name: file_name.to_string()
+        }
+    }
+
+    pub fn load(&self) -> Result<Mesh, MeshError> {
+        let text = try!(read_text(Path::new(&self.file_name)));
+
+        let mut parser = ObjParser {
+            file_name: &self.file_name,
+            directory: parent_directory(Path::new(&self.file_name)),
+            mesh: Mesh::new(),
+            material_indices: HashMap::new(),
+            current_material: None,
+            current_group: None
+        };
+        try!(parser.parse(&text));
+
+        Ok(parser.mesh)
+    }
+}
+
+impl<'a> ObjParser<'a> {
+    fn parse(&mut self, text: &str) -> Result<(), MeshError> {
+        for (line_number, line) in logical_lines(text) {
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            if tokens.is_empty() {
+                continue;
+            }
+
+            let arguments = &tokens[1..];
+            match tokens[0] {
+                "v" => {
+                    let values = try!(self.parse_numbers(line_number, arguments, 3));
+                    self.mesh.positions.push(Point3D::from_xyz(values[0], values[1], values[2]));
+                },
+                "vt" => {
+                    let values = try!(self.parse_numbers(line_number, arguments, 1));
+                    let v = if values.len() > 1 { values[1] } else { 0.0 };
+                    self.mesh.uvs.push((values[0], v));
+                },
+                "vn" => {
+                    let values = try!(self.parse_numbers(line_number, arguments, 3));
+                    self.mesh.normals.push(Direction3D::from_xyz(values[0], values[1], values[2]));
+                },
+                "f" => try!(self.parse_face(line_number, arguments)),
+                "g" | "o" => {
+                    let name = arguments.join(" ");
+                    let index = match self.mesh.groups.iter().position(|group| *group == name) {
+                        Some(index) => index,
+                        None => {
+                            self.mesh.groups.push(name);
+                            self.mesh.groups.len() - 1
+                        }
+                    };
+                    self.current_group = Some(index);
+                },
+                "usemtl" => {
+                    let name = arguments.join(" ");
+                    match self.material_indices.get(&name) {
+                        Some(&index) => self.current_material = Some(index),
+                        None => return Err(self.error(line_number, format!("unknown material `{}`", name)))
+                    }
+                },
+                "mtllib" => {
+                    for library in arguments {
+                        try!(self.load_material_library(library));
+                    }
+                },
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_face(&mut self, line_number: usize, arguments: &[&str]) -> Result<(), MeshError> {
+        if arguments.len() < 3 {
+            return Err(self.error(line_number, format!("face has {} vertices, expected at least 3", arguments.len())));
+        }
+
+        let mut corners = Vec::with_capacity(arguments.len());
+        for argument in arguments {
+            corners.push(try!(self.parse_corner(line_number, argument)));
+        }
+
+        // Attributes are only used if every corner of the face has them.
+        let has_uvs = corners.iter().all(|corner| corner.uv.is_some());
+        let has_normals = corners.iter().all(|corner| corner.normal.is_some());
+
+        for index in 1..corners.len() - 1 {
+            let triangle = [&corners[0], &corners[index], &corners[index + 1]];
+            self.mesh.faces.push(MeshFace {
+                positions: [triangle[0].position, triangle[1].position, triangle[2].position],
+                normals: if has_normals {
+                    Some([triangle[0].normal.unwrap(), triangle[1].normal.unwrap(), triangle[2].normal.unwrap()])
+                } else {
+                    None
+                },
+                uvs: if has_uvs {
+                    Some([triangle[0].uv.unwrap(), triangle[1].uv.unwrap(), triangle[2].uv.unwrap()])
+                } else {
+                    None
+                },
+                material: self.current_material,
+                group: self.current_group
+            });
+        }
+
+        Ok(())
+    }
+
+    fn parse_corner(&self, line_number: usize, argument: &str) -> Result<FaceCorner, MeshError> {
+        let mut parts = argument.split('/');
+
+        let position = match parts.next() {
+            Some(part) => try!(self.resolve_index(line_number, part, self.mesh.positions.len(), "vertex")),
+            None => return Err(self.error(line_number, format!("invalid face vertex `{}`", argument)))
+        };
+        let uv = match parts.next() {
+            Some(part) if !part.is_empty() => Some(try!(self.resolve_index(line_number, part, self.mesh.uvs.len(), "texture coordinate"))),
+            _ => None
+        };
+        let normal = match parts.next() {
+            Some(part) if !part.is_empty() => Some(try!(self.resolve_index(line_number, part, self.mesh.normals.len(), "normal"))),
+            _ => None
+        };
+
+        Ok(FaceCorner {
+            position: position,
+            uv: uv,
+            normal: normal
+        })
+    }
+
+    // OBJ indices start at 1, and negative indices count back from the most
+    // recently defined element.
+    fn resolve_index(&self, line_number: usize, text: &str, count: usize, kind: &str) -> Result<usize, MeshError> {
+        let index = match text.parse::<isize>() {
+            Ok(index) => index,
+            Err(_) => return Err(self.error(line_number, format!("invalid {} index `{}`", kind, text)))
+        };
+
+        let resolved = if index > 0 {
+            index - 1
+        } else {
+            count as isize + index
+        };
+        if index == 0 || resolved < 0 || resolved >= count as isize {
+            return Err(self.error(line_number, format!("{} index {} is out of range", kind, index)));
+        }
+
+        Ok(resolved as usize)
+    }
+
+    fn load_material_library(&mut self, library: &str) -> Result<(), MeshError> {
+        let path = self.directory.join(library);
+        let file_name = path.to_string_lossy().into_owned();
+        let text = try!(read_text(&path));
+
+        let mut current: Option<(String, MaterialBuilder)> = None;
+        for (line_number, line) in logical_lines(&text) {
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            if tokens.is_empty() {
+                continue;
+            }
+
+            let arguments = &tokens[1..];
+            if tokens[0] == "newmtl" {
+                if let Some((name, builder)) = current.take() {
+                    self.add_material(name, &builder);
+                }
+                let mut builder = MaterialBuilder::new();
+                // MTL materials default to the index of refraction of a vacuum.
+                builder.refractive_index(1.0);
+                current = Some((arguments.join(" "), builder));
+                continue;
+            }
+
+            let builder = match current {
+                Some((_, ref mut builder)) => builder,
+                None => continue
+            };
+            match tokens[0] {
+                "Kd" => {
+                    let values = try!(parse_numbers(&file_name, line_number, arguments, 3));
+                    builder.color(&ColorRGB::from_rgb(values[0], values[1], values[2]));
+                },
+                "Ks" => {
+                    let values = try!(parse_numbers(&file_name, line_number, arguments, 3));
+                    builder.specular((values[0] + values[1] + values[2]) / 3.0);
+                },
+                "Ns" => {
+                    let values = try!(parse_numbers(&file_name, line_number, arguments, 1));
+                    builder.shininess(values[0].max(0.0).round() as u32);
+                },
+                "Ni" => {
+                    let values = try!(parse_numbers(&file_name, line_number, arguments, 1));
+                    builder.refractive_index(values[0]);
+                },
+                "d" => {
+                    let values = try!(parse_numbers(&file_name, line_number, arguments, 1));
+                    builder.refraction(1.0 - values[0]);
+                },
+                "Tr" => {
+                    let values = try!(parse_numbers(&file_name, line_number, arguments, 1));
+                    builder.refraction(values[0]);
+                },
+                _ => {}
+            }
+        }
+
+        if let Some((name, builder)) = current.take() {
+            self.add_material(name, &builder);
+        }
+        Ok(())
+    }
+
+    fn add_material(&mut self, name: String, builder: &MaterialBuilder) {
+        self.mesh.materials.push(builder.to_material());
+        self.material_indices.insert(name, self.mesh.materials.len() - 1);
+    }
+
+    fn parse_numbers(&self, line_number: usize, arguments: &[&str], minimum: usize) -> Result<Vec<f32>, MeshError> {
+        parse_numbers(self.file_name, line_number, arguments, minimum)
+    }
+
+    fn error(&self, line_number: usize, message: String) -> MeshError {
+        MeshError::parse(self.file_name, line_number, message)
+    }
+}
+
+fn parse_numbers(file_name: &str, line_number: usize, arguments: &[&str], minimum: usize) -> Result<Vec<f32>, MeshError> {
+    if arguments.len() < minimum {
+        return Err(MeshError::parse(file_name, line_number, format!("expected {} values, found {}", minimum, arguments.len())));
+    }
+
+    let mut values = Vec::with_capacity(arguments.len());
+    for argument in arguments {
+        match f32::from_str(argument) {
+            Ok(value) => values.push(value),
+            Err(_) => return Err(MeshError::parse(file_name, line_number, format!("invalid number `{}`", argument)))
+        }
+    }
+    Ok(values)
+}
+
+// Strips comments and joins lines ending in a backslash. Each line is returned
+// with the number of the line on which it starts.
+fn logical_lines(text: &str) -> Vec<(usize, String)> {
+    let mut lines = Vec::new();
+    let mut pending: Option<(usize, String)> = None;
+
+    for (index, raw_line) in text.lines().enumerate() {
+        let line = match raw_line.find('#') {
+            Some(position) => &raw_line[..position],
+            None => raw_line
+        };
+        let trimmed = line.trim_end();
+        let continues = trimmed.ends_with('\\');
+        let line = if continues {
+            &trimmed[..trimmed.len() - 1]
+        } else {
+            line
+        };
+
+        let (line_number, mut joined) = pending.take().unwrap_or_else(|| (index + 1, String::new()));
+        joined.push(' ');
+        joined.push_str(line);
+
+        if continues {
+            pending = Some((line_number, joined));
+        } else {
+            lines.push((line_number, joined));
+        }
+    }
+    if let Some(line) = pending {
+        lines.push(line);
+    }
+
+    lines
+}
+
+fn read_text(path: &Path) -> Result<String, MeshError> {
+    let mut file = try!(File::open(path));
+    let mut text = String::new();
+    try!(file.read_to_string(&mut text));
+    Ok(text)
+}
+
+fn parent_directory(path: &Path) -> PathBuf {
+    match path.parent() {
+        Some(parent) => parent.to_path_buf(),
+        None => PathBuf::new()
+    }
+}
@@ -0,0 +1,552 @@
+use std::fs::{File};
+use std::io::{Read};
+use std::path::{Path};
+
+use color::{ColorRGB};
+use geometry::{Point3D, Direction3D};
+
+use super::{Mesh, MeshFace, MeshError};
+
+// Stanford PLY reader for ASCII and binary (little and big endian) files.
+// Vertex positions, normals, texture coordinates and colors are read from the
+// `vertex` element and polygons from the `face` element; polygons are
+// triangulated as fans. Other elements and properties are skipped.
+#[derive(Debug)]
+pub struct PlyFile {
+    file_name: String
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum PlyFormat {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum ScalarType {
+    Int8,
+    UInt8,
+    Int16,
+    UInt16,
+    Int32,
+    UInt32,
+    Float32,
+    Float64
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+enum PropertyType {
+    Scalar(ScalarType),
+    List(ScalarType, ScalarType)
+}
+
+#[derive(Debug)]
+struct Property {
+    name: String,
+    property_type: PropertyType
+}
+
+#[derive(Debug)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>
+}
+
+impl PlyFile {
+    pub fn new(file_name: &str) -> PlyFile {
+        PlyFile {
+            file_name: file_name.to_string()
+        }
+    }
+
+    pub fn load(&self) -> Result<Mesh, MeshError> {
+        let file_path = Path::new(&self.file_name);
+
+        let mut file = try!(File::open(file_path));
+        let mut data = Vec::new();
+        try!(file.read_to_end(&mut data));
+        self.decode(&data)
+    }
+
+    // Builds the mesh from the contents of the file.
+    fn decode(&self, data: &[u8]) -> Result<Mesh, MeshError> {
+        let (format, elements, data_start, data_line) = try!(self.parse_header(data));
+
+        let mut mesh = Mesh::new();
+        match format {
+            PlyFormat::Ascii => {
+                let text = String::from_utf8_lossy(&data[data_start..]);
+                let mut reader = AsciiReader::new(&self.file_name, &text, data_line);
+                try!(self.read_elements(&elements, &mut reader, &mut mesh));
+            },
+            PlyFormat::BinaryLittleEndian | PlyFormat::BinaryBigEndian => {
+                let mut reader = BinaryReader {
+                    file_name: &self.file_name,
+                    data: &data[data_start..],
+                    position: 0,
+                    big_endian: format == PlyFormat::BinaryBigEndian
+                };
+                try!(self.read_elements(&elements, &mut reader, &mut mesh));
+            }
+        }
+
+        for face in &mesh.faces {
+            for &index in &face.positions {
+                if index >= mesh.positions.len() {
+                    return Err(MeshError::format(&self.file_name, format!("vertex index {} is out of range", index)));
+                }
+            }
+        }
+
+        Ok(mesh)
+    }
+
+    // Returns the format, the elements, the offset of the first byte after the
+    // header and the line on which the data starts.
+    fn parse_header(&self, data: &[u8]) -> Result<(PlyFormat, Vec<Element>, usize, usize), MeshError> {
+        let mut format = None;
+        let mut elements: Vec<Element> = Vec::new();
+        let mut position = 0;
+        let mut line_number = 0;
+
+        loop {
+            if position >= data.len() {
+                return Err(MeshError::format(&self.file_name, "missing `end_header`".to_string()));
+            }
+
+            let line_end = data[position..].iter().position(|&byte| byte == b'\n').map_or(data.len(), |offset| position + offset);
+            let line = String::from_utf8_lossy(&data[position..line_end]).into_owned();
+            position = (line_end + 1).min(data.len());
+            line_number += 1;
+
+            let tokens = line.split_whitespace().collect::<Vec<_>>();
+            if line_number == 1 {
+                if tokens != ["ply"] {
+                    return Err(MeshError::parse(&self.file_name, line_number, "not a PLY file".to_string()));
+                }
+                continue;
+            }
+            if tokens.is_empty() {
+                continue;
+            }
+
+            match tokens[0] {
+                "format" => {
+                    if tokens.len() < 3 {
+                        return Err(MeshError::parse(&self.file_name, line_number, "invalid `format` line".to_string()));
+                    }
+                    format = match tokens[1] {
+                        "ascii" => Some(PlyFormat::Ascii),
+                        "binary_little_endian" => Some(PlyFormat::BinaryLittleEndian),
+                        "binary_big_endian" => Some(PlyFormat::BinaryBigEndian),
+                        _ => return Err(MeshError::parse(&self.file_name, line_number, format!("unsupported format `{}`", tokens[1])))
+                    };
+                },
+                "element" => {
+                    if tokens.len() != 3 {
+                        return Err(MeshError::parse(&self.file_name, line_number, "invalid `element` line".to_string()));
+                    }
+                    let count = match tokens[2].parse::<usize>() {
+                        Ok(count) => count,
+                        Err(_) => return Err(MeshError::parse(&self.file_name, line_number, format!("invalid element count `{}`", tokens[2])))
+                    };
+                    elements.push(Element {
+                        name: tokens[1].to_string(),
+                        count: count,
+                        properties: Vec::new()
+                    });
+                },
+                "property" => {
+                    let property = try!(self.parse_property(line_number, &tokens[1..]));
+                    match elements.last_mut() {
+                        Some(element) => element.properties.push(property),
+                        None => return Err(MeshError::parse(&self.file_name, line_number, "`property` before any `element`".to_string()))
+                    }
+                },
+                "end_header" => break,
+                "comment" | "obj_info" => {},
+                _ => return Err(MeshError::parse(&self.file_name, line_number, format!("unknown header line `{}`", tokens[0])))
+            }
+        }
+
+        match format {
+            Some(format) => Ok((format, elements, position, line_number + 1)),
+            None => Err(MeshError::format(&self.file_name, "missing `format` line".to_string()))
+        }
+    }
+
+    fn parse_property(&self, line_number: usize, tokens: &[&str]) -> Result<Property, MeshError> {
+        let parse_type = |name: &str| -> Result<ScalarType, MeshError> {
+            match name {
+                "char" | "int8" => Ok(ScalarType::Int8),
+                "uchar" | "uint8" => Ok(ScalarType::UInt8),
+                "short" | "int16" => Ok(ScalarType::Int16),
+                "ushort" | "uint16" => Ok(ScalarType::UInt16),
+                "int" | "int32" => Ok(ScalarType::Int32),
+                "uint" | "uint32" => Ok(ScalarType::UInt32),
+                "float" | "float32" => Ok(ScalarType::Float32),
+                "double" | "float64" => Ok(ScalarType::Float64),
+                _ => Err(MeshError::parse(&self.file_name, line_number, format!("unsupported property type `{}`", name)))
+            }
+        };
+
+        match tokens {
+            ["list", count_type, item_type, name] => {
+                let count_type = try!(parse_type(count_type));
+                if count_type == ScalarType::Float32 || count_type == ScalarType::Float64 {
+                    return Err(MeshError::parse(&self.file_name, line_number, format!("unsupported list count type for property `{}`", name)));
+                }
+                Ok(Property {
+                    name: name.to_string(),
+                    property_type: PropertyType::List(count_type, try!(parse_type(item_type)))
+                })
+            },
+            [scalar_type, name] => {
+                Ok(Property {
+                    name: name.to_string(),
+                    property_type: PropertyType::Scalar(try!(parse_type(scalar_type)))
+                })
+            },
+            _ => Err(MeshError::parse(&self.file_name, line_number, "invalid `property` line".to_string()))
+        }
+    }
+
+    fn read_elements<R: ValueReader>(&self, elements: &[Element], reader: &mut R, mesh: &mut Mesh) -> Result<(), MeshError> {
+        for element in elements {
+            match &*element.name {
+                "vertex" => try!(self.read_vertices(element, reader, mesh)),
+                "face" => try!(self.read_faces(element, reader, mesh)),
+                _ => {
+                    for _ in 0..element.count {
+                        for property in &element.properties {
+                            try!(read_property(reader, property));
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn read_vertices<R: ValueReader>(&self, element: &Element, reader: &mut R, mesh: &mut Mesh) -> Result<(), MeshError> {
+        let find = |names: &[&str]| element.properties.iter().position(|property| names.contains(&&*property.name));
+
+        let position_indices = [find(&["x"]), find(&["y"]), find(&["z"])];
+        let normal_indices = [find(&["nx"]), find(&["ny"]), find(&["nz"])];
+        let uv_indices = [find(&["u", "s", "texture_u", "texture_s"]), find(&["v", "t", "texture_v", "texture_t"])];
+        let color_indices = [find(&["red", "diffuse_red", "r"]), find(&["green", "diffuse_green", "g"]), find(&["blue", "diffuse_blue", "b"])];
+
+        if position_indices.iter().any(|index| index.is_none()) {
+            return Err(MeshError::format(&self.file_name, "`vertex` element requires `x`, `y` and `z` properties".to_string()));
+        }
+        let has_normals = normal_indices.iter().all(|index| index.is_some());
+        let has_uvs = uv_indices.iter().all(|index| index.is_some());
+        let has_colors = color_indices.iter().all(|index| index.is_some());
+
+        // Integer colors are scaled by the largest value of their type.
+        let color_scales = color_indices.iter().map(|index| {
+            match index.map(|index| element.properties[index].property_type) {
+                Some(PropertyType::Scalar(scalar_type)) => 1.0 / max_value(scalar_type),
+                _ => 1.0
+            }
+        }).collect::<Vec<_>>();
+
+        let mut values = vec![0.0; element.properties.len()];
+        for _ in 0..element.count {
+            for (value, property) in values.iter_mut().zip(element.properties.iter()) {
+                *value = match try!(read_property(reader, property)) {
+                    Some(scalar) => scalar as f32,
+                    None => 0.0
+                };
+            }
+
+            let value = |index: Option<usize>| values[index.unwrap()];
+            mesh.positions.push(Point3D::from_xyz(value(position_indices[0]), value(position_indices[1]), value(position_indices[2])));
+            if has_normals {
+                mesh.normals.push(Direction3D::from_xyz(value(normal_indices[0]), value(normal_indices[1]), value(normal_indices[2])));
+            }
+            if has_uvs {
+                mesh.uvs.push((value(uv_indices[0]), value(uv_indices[1])));
+            }
+            if has_colors {
+                mesh.colors.push(ColorRGB::from_rgb(
+                    value(color_indices[0]) * color_scales[0],
+                    value(color_indices[1]) * color_scales[1],
+                    value(color_indices[2]) * color_scales[2]
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_faces<R: ValueReader>(&self, element: &Element, reader: &mut R, mesh: &mut Mesh) -> Result<(), MeshError> {
+        let index_property = element.properties.iter().position(|property| {
+            (property.name == "vertex_indices" || property.name == "vertex_index") &&
+            matches!(property.property_type, PropertyType::List(_, _))
+        });
+        let index_property = match index_property {
+            Some(index_property) => index_property,
+            None => return Err(MeshError::format(&self.file_name, "`face` element requires a `vertex_indices` list property".to_string()))
+        };
+
+        // Per-vertex attributes share the position indices.
+        let has_normals = !mesh.normals.is_empty();
+        let has_uvs = !mesh.uvs.is_empty();
+
+        let mut indices = Vec::new();
+        for _ in 0..element.count {
+            for (property_index, property) in element.properties.iter().enumerate() {
+                if property_index != index_property {
+                    try!(read_property(reader, property));
+                    continue;
+                }
+
+                let (count_type, item_type) = match property.property_type {
+                    PropertyType::List(count_type, item_type) => (count_type, item_type),
+                    PropertyType::Scalar(_) => unreachable!()
+                };
+                let count = try!(reader.read(count_type)) as usize;
+                indices.clear();
+                for _ in 0..count {
+                    let index = try!(reader.read(item_type));
+                    if index < 0.0 {
+                        return Err(MeshError::format(&self.file_name, format!("negative vertex index {}", index)));
+                    }
+                    indices.push(index as usize);
+                }
+            }
+
+            for index in 1..indices.len().saturating_sub(1) {
+                let positions = [indices[0], indices[index], indices[index + 1]];
+                mesh.faces.push(MeshFace {
+                    positions: positions,
+                    normals: if has_normals { Some(positions) } else { None },
+                    uvs: if has_uvs { Some(positions) } else { None },
+                    material: None,
+                    group: None
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Reads a property and returns its value, or `None` for lists.
+fn read_property<R: ValueReader>(reader: &mut R, property: &Property) -> Result<Option<f64>, MeshError> {
+    match property.property_type {
+        PropertyType::Scalar(scalar_type) => Ok(Some(try!(reader.read(scalar_type)))),
+        PropertyType::List(count_type, item_type) => {
+            let count = try!(reader.read(count_type)) as usize;
+            for _ in 0..count {
+                try!(reader.read(item_type));
+            }
+            Ok(None)
+        }
+    }
+}
+
+fn max_value(scalar_type: ScalarType) -> f32 {
+    match scalar_type {
+        ScalarType::Int8 => 127.0,
+        ScalarType::UInt8 => 255.0,
+        ScalarType::Int16 => 32767.0,
+        ScalarType::UInt16 => 65535.0,
+        ScalarType::Int32 => 2147483647.0,
+        ScalarType::UInt32 => 4294967295.0,
+        ScalarType::Float32 | ScalarType::Float64 => 1.0
+    }
+}
+
+//------------------------------------------------------------------------------
+
+trait ValueReader {
+    fn read(&mut self, scalar_type: ScalarType) -> Result<f64, MeshError>;
+}
+
+struct AsciiReader<'a> {
+    file_name: &'a str,
+    tokens: Vec<(&'a str, usize)>,
+    position: usize
+}
+
+impl<'a> AsciiReader<'a> {
+    fn new(file_name: &'a str, text: &'a str, first_line: usize) -> AsciiReader<'a> {
+        let mut tokens = Vec::new();
+        for (index, line) in text.lines().enumerate() {
+            for token in line.split_whitespace() {
+                tokens.push((token, first_line + index));
+            }
+        }
+
+        AsciiReader {
+            file_name: file_name,
+            tokens: tokens,
+            position: 0
+        }
+    }
+}
+
+impl<'a> ValueReader for AsciiReader<'a> {
+    fn read(&mut self, scalar_type: ScalarType) -> Result<f64, MeshError> {
+        let (token, line_number) = match self.tokens.get(self.position) {
+            Some(&token) => token,
+            None => return Err(MeshError::format(self.file_name, "unexpected end of data".to_string()))
+        };
+        self.position += 1;
+
+        let value = match scalar_type {
+            ScalarType::Float32 | ScalarType::Float64 => token.parse::<f64>().ok(),
+            _ => token.parse::<i64>().ok().map(|value| value as f64)
+        };
+        match value {
+            Some(value) => Ok(value),
+            None => Err(MeshError::parse(self.file_name, line_number, format!("invalid value `{}`", token)))
+        }
+    }
+}
+
+struct BinaryReader<'a> {
+    file_name: &'a str,
+    data: &'a [u8],
+    position: usize,
+    big_endian: bool
+}
+
+impl<'a> ValueReader for BinaryReader<'a> {
+    fn read(&mut self, scalar_type: ScalarType) -> Result<f64, MeshError> {
+        let size = match scalar_type {
+            ScalarType::Int8 | ScalarType::UInt8 => 1,
+            ScalarType::Int16 | ScalarType::UInt16 => 2,
+            ScalarType::Int32 | ScalarType::UInt32 | ScalarType::Float32 => 4,
+            ScalarType::Float64 => 8
+        };
+        if self.position + size > self.data.len() {
+            return Err(MeshError::format(self.file_name, "unexpected end of data".to_string()));
+        }
+
+        let mut bytes = [0u8; 8];
+        bytes[..size].copy_from_slice(&self.data[self.position..self.position + size]);
+        if self.big_endian {
+            bytes[..size].reverse();
+        }
+        self.position += size;
+
+        let value = match scalar_type {
+            ScalarType::Int8 => bytes[0] as i8 as f64,
+            ScalarType::UInt8 => bytes[0] as f64,
+            ScalarType::Int16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            ScalarType::UInt16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+            ScalarType::Int32 => i32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ScalarType::UInt32 => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ScalarType::Float32 => f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]) as f64,
+            ScalarType::Float64 => f64::from_le_bytes(bytes)
+        };
+        Ok(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mesh::{Mesh};
+
+    use super::{PlyFile};
+
+    const HEADER: &str = "\
+element vertex 3
+property float x
+property float y
+property float z
+property uchar red
+property uchar green
+property uchar blue
+element face 1
+property list uchar int vertex_indices
+end_header
+";
+
+    fn decode(format: &str, body: &[u8]) -> Mesh {
+        let mut data = format!("ply\nformat {} 1.0\n{}", format, HEADER).into_bytes();
+        data.extend_from_slice(body);
+        PlyFile::new("test.ply").decode(&data).unwrap()
+    }
+
+    // The vertices of `HEADER` at x = 0, 1 and 2 with red values of 255, 0
+    // and 51, and a triangle through them.
+    fn binary_body(big_endian: bool) -> Vec<u8> {
+        let mut body = Vec::new();
+        for &(x, red) in &[(0.0f32, 255u8), (1.0, 0), (2.0, 51)] {
+            for &value in &[x, 0.0, 0.0] {
+                if big_endian {
+                    body.extend_from_slice(&value.to_be_bytes());
+                } else {
+                    body.extend_from_slice(&value.to_le_bytes());
+                }
+            }
+            body.extend_from_slice(&[red, 0, 0]);
+        }
+        body.push(3);
+        for &index in &[0i32, 1, 2] {
+            if big_endian {
+                body.extend_from_slice(&index.to_be_bytes());
+            } else {
+                body.extend_from_slice(&index.to_le_bytes());
+            }
+        }
+        body
+    }
+
+    fn check(mesh: &Mesh) {
+        assert_eq!(mesh.positions.len(), 3);
+        assert_eq!(mesh.positions[2].x, 2.0);
+        assert_eq!(mesh.faces.len(), 1);
+        assert_eq!(mesh.faces[0].positions, [0, 1, 2]);
+
+        // Colors stored as bytes are scaled to [0, 1].
+        assert_eq!(mesh.colors.len(), 3);
+        assert_eq!(mesh.colors[0].red, 1.0);
+        assert_eq!(mesh.colors[1].red, 0.0);
+        assert!((mesh.colors[2].red - 0.2).abs() < 1.0e-6);
+    }
+
+    #[test]
+    fn reads_ascii() {
+        check(&decode("ascii", b"0 0 0 255 0 0\n1 0 0 0 0 0\n2 0 0 51 0 0\n3 0 1 2\n"));
+    }
+
+    #[test]
+    fn reads_binary_little_endian() {
+        check(&decode("binary_little_endian", &binary_body(false)));
+    }
+
+    #[test]
+    fn reads_binary_big_endian() {
+        check(&decode("binary_big_endian", &binary_body(true)));
+    }
+
+    #[test]
+    fn triangulates_polygons_as_fans() {
+        let data = b"ply\nformat ascii 1.0\nelement vertex 4\nproperty float x\nproperty float y\nproperty float z\n\
+element face 1\nproperty list uchar int vertex_indices\nend_header\n0 0 0\n1 0 0\n1 1 0\n0 1 0\n4 0 1 2 3\n";
+        let mesh = PlyFile::new("test.ply").decode(data).unwrap();
+        assert_eq!(mesh.faces.len(), 2);
+        assert_eq!(mesh.faces[1].positions, [0, 2, 3]);
+        assert!(mesh.colors.is_empty());
+    }
+
+    #[test]
+    fn rejects_truncated_binary_data() {
+        let mut data = format!("ply\nformat binary_little_endian 1.0\n{}", HEADER).into_bytes();
+        data.extend_from_slice(&binary_body(false)[..20]);
+        assert!(PlyFile::new("test.ply").decode(&data).is_err());
+    }
+
+    #[test]
+    fn rejects_out_of_range_indices() {
+        assert!(PlyFile::new("test.ply").decode(b"ply\nformat ascii 1.0\nelement vertex 1\nproperty float x\nproperty float y\nproperty float z\n\
+element face 1\nproperty list uchar int vertex_indices\nend_header\n0 0 0\n3 0 1 2\n").is_err());
+    }
+}
@@ -0,0 +1,182 @@
+use std::path::{Path};
+use std::str::{FromStr};
+
+use ray_tracer::image::{BitDepth, ToneMapping, ToneMapOperator, Clamp, Reinhard, Filmic, TransferFunction};
+use ray_tracer::render::{AntiAliasing, Verbosity};
+
+pub const USAGE: &str = "\
+Usage: rust_ray_tracer [options] <scene-file>
+
+Options:
+    -o, --output <file>          output image file: .ppm, .png, .pfm or .hdr
+                                 (default: <scene-name>.ppm)
+    -b, --bit-depth <bits>       bits per channel for PNG output: 8 or 16 (default: 8)
+    -T, --tone-map <operator>    tone mapping for .ppm and .png output: clamp, reinhard
+                                 or filmic (default: clamp)
+    -e, --exposure <stops>       exposure adjustment applied before tone mapping
+                                 (default: 0)
+    -g, --gamma <encoding>       output encoding: linear, srgb or a gamma value such
+                                 as 2.2 (default: linear)
+    -W, --width <pixels>         override the image width from the scene file
+    -H, --height <pixels>        override the image height from the scene file
+    -t, --threads <count>        number of render threads (default: number of CPUs)
+    -d, --max-depth <depth>      override the maximum ray depth from the scene file
+    -a, --anti-aliasing <mode>   anti-aliasing mode: none, edge or full (default: edge)
+    -q, --quiet                  do not print any timing information
+    -v, --verbose                print timing information for every render stage
+    -h, --help                   print this message";
+
+#[derive(Debug)]
+pub struct Options {
+    pub scene_file: String,
+    pub output_file: String,
+    pub bit_depth: BitDepth,
+    pub tone_mapping: ToneMapping,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+    pub threads: Option<usize>,
+    pub max_ray_depth: Option<u32>,
+    pub anti_aliasing: AntiAliasing,
+    pub verbosity: Verbosity,
+    pub show_help: bool
+}
+
+impl Options {
+    pub fn parse<T: Iterator<Item=String>>(args: T) -> Result<Options, String> {
+        let mut scene_file = None;
+        let mut output_file = None;
+        let mut bit_depth = BitDepth::Eight;
+        let mut tone_map_operator: Box<ToneMapOperator> = Box::new(Clamp);
+        let mut exposure = 0.0;
+        let mut transfer_function = TransferFunction::Linear;
+        let mut width = None;
+        let mut height = None;
+        let mut threads = None;
+        let mut max_ray_depth = None;
+        let mut anti_aliasing = AntiAliasing::EdgeDetect;
+        let mut verbosity = Verbosity::Normal;
+        let mut show_help = false;
+
+        let mut args = args;
+        while let Some(arg) = args.next() {
+            match &*arg {
+                "-o" | "--output" => output_file = Some(try!(next_value(&mut args, &arg))),
+                "-b" | "--bit-depth" => {
+                    let value = try!(next_value(&mut args, &arg));
+                    bit_depth = match &*value {
+                        "8" => BitDepth::Eight,
+                        "16" => BitDepth::Sixteen,
+                        _ => return Err(format!("invalid bit depth `{}`", value))
+                    };
+                },
+                "-T" | "--tone-map" => {
+                    let value = try!(next_value(&mut args, &arg));
+                    tone_map_operator = match &*value {
+                        "clamp" => Box::new(Clamp),
+                        "reinhard" => Box::new(Reinhard::new(4.0)),
+                        "filmic" => Box::new(Filmic),
+                        _ => return Err(format!("invalid tone mapping operator `{}`", value))
+                    };
+                },
+                "-e" | "--exposure" => exposure = try!(parse_value(&mut args, &arg)),
+                "-g" | "--gamma" => {
+                    let value = try!(next_value(&mut args, &arg));
+                    transfer_function = match &*value {
+                        "linear" => TransferFunction::Linear,
+                        "srgb" => TransferFunction::SRGB,
+                        _ => match value.parse::<f32>() {
+                            Ok(gamma) if gamma > 0.0 => TransferFunction::Gamma(gamma),
+                            _ => return Err(format!("invalid output encoding `{}`", value))
+                        }
+                    };
+                },
+                "-W" | "--width" => width = Some(try!(parse_positive(&mut args, &arg))),
+                "-H" | "--height" => height = Some(try!(parse_positive(&mut args, &arg))),
+                "-t" | "--threads" => threads = Some(try!(parse_positive(&mut args, &arg))),
+                "-d" | "--max-depth" => max_ray_depth = Some(try!(parse_value(&mut args, &arg))),
+                "-a" | "--anti-aliasing" => {
+                    let value = try!(next_value(&mut args, &arg));
+                    anti_aliasing = match &*value {
+                        "none" => AntiAliasing::None,
+                        "edge" => AntiAliasing::EdgeDetect,
+                        "full" => AntiAliasing::Full,
+                        _ => return Err(format!("invalid anti-aliasing mode `{}`", value))
+                    };
+                },
+                "-q" | "--quiet" => verbosity = Verbosity::Quiet,
+                "-v" | "--verbose" => verbosity = Verbosity::Verbose,
+                "-h" | "--help" => show_help = true,
+                _ => {
+                    if arg.starts_with("-") {
+                        return Err(format!("unknown option `{}`", arg));
+                    }
+                    if scene_file.is_some() {
+                        return Err(format!("unexpected argument `{}`", arg));
+                    }
+                    scene_file = Some(arg);
+                }
+            }
+        }
+
+        let scene_file = match scene_file {
+            Some(scene_file) => scene_file,
+            None => {
+                if show_help {
+                    String::new()
+                } else {
+                    return Err("missing scene file".to_string());
+                }
+            }
+        };
+
+        let output_file = match output_file {
+            Some(output_file) => output_file,
+            None => default_output_file(&scene_file)
+        };
+
+        Ok(Options {
+            scene_file: scene_file,
+            output_file: output_file,
+            bit_depth: bit_depth,
+            tone_mapping: ToneMapping::new(exposure, tone_map_operator, transfer_function),
+            width: width,
+            height: height,
+            threads: threads,
+            max_ray_depth: max_ray_depth,
+            anti_aliasing: anti_aliasing,
+            verbosity: verbosity,
+            show_help: show_help
+        })
+    }
+}
+
+fn default_output_file(scene_file: &str) -> String {
+    let stem = match Path::new(scene_file).file_stem() {
+        Some(stem) => stem.to_string_lossy().into_owned(),
+        None => "output".to_string()
+    };
+    format!("{}.ppm", stem)
+}
+
+fn next_value<T: Iterator<Item=String>>(args: &mut T, option: &str) -> Result<String, String> {
+    match args.next() {
+        Some(value) => Ok(value),
+        None => Err(format!("option `{}` requires a value", option))
+    }
+}
+
+fn parse_value<T: Iterator<Item=String>, U: FromStr>(args: &mut T, option: &str) -> Result<U, String> {
+    let value = try!(next_value(args, option));
+    match value.parse::<U>() {
+        Ok(result) => Ok(result),
+        Err(_) => Err(format!("invalid value `{}` for option `{}`", value, option))
+    }
+}
+
+fn parse_positive<T: Iterator<Item=String>>(args: &mut T, option: &str) -> Result<usize, String> {
+    let value: usize = try!(parse_value(args, option));
+    if value == 0 {
+        return Err(format!("option `{}` must be greater than zero", option));
+    }
+    Ok(value)
+}
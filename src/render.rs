@@ -0,0 +1,187 @@
+use std::sync::{Arc};
+use std::thread;
+
+use num_cpus;
+use time;
+
+use color::{ColorRGB};
+use geometry::{Point3D, Direction3D, Ray3D};
+use scene::{Scene, Camera};
+use table::{Table, AsTable};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AntiAliasing {
+    None,
+    EdgeDetect,
+    Full
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose
+}
+
+#[derive(Clone, Debug)]
+pub struct RenderOptions {
+    pub num_threads: usize,
+    pub anti_aliasing: AntiAliasing,
+    pub verbosity: Verbosity
+}
+
+impl RenderOptions {
+    pub fn new() -> RenderOptions {
+        RenderOptions {
+            num_threads: num_cpus::get(),
+            anti_aliasing: AntiAliasing::EdgeDetect,
+            verbosity: Verbosity::Normal
+        }
+    }
+}
+
+impl Default for RenderOptions {
+    fn default() -> RenderOptions {
+        RenderOptions::new()
+    }
+}
+
+pub fn render(scene: Arc<Scene>, camera: Arc<Camera>, options: &RenderOptions) -> Table<ColorRGB> {
+    let dimensions = camera.get_image_dimensions();
+    let (width, height) = dimensions;
+
+    let mut pixel_table = Table::from_elem(dimensions, ColorRGB::black().clone());
+
+    // Initial Pixel Coloring
+    // let intital_coloring_start = time::precise_time_ns();
+    // for (index, value) in pixel_table.iter_mut().enumerate_2d() {
+    //     let ray = camera.get_primary_ray(index);
+    //     let result = scene.trace(&ray, 0);
+        
+    //     let result_color = ColorRGB::from_rgb(
+    //         result.color.red.min(1.0),
+    //         result.color.green.min(1.0),
+    //         result.color.blue.min(1.0)
+    //     );
+    //     *value = result_color;
+    // }
+    // let initial_coloring_end = time::precise_time_ns();
+
+    let thread_setup_start = time::precise_time_ns();
+    let total_pixels = width * height;
+    let num_threads = options.num_threads.min(total_pixels).max(1);
+    let pixels_per_thread = if total_pixels % num_threads > 0 {
+        total_pixels / num_threads + 1
+    } else {
+        total_pixels / num_threads
+    };
+
+    // Initial Pixel Coloring
+    let initial_coloring_threads = (0..num_threads).map(|thread_index| {
+        let local_camera = camera.clone();
+        let local_scene = scene.clone();
+        thread::spawn(move|| {
+            let start_index = (pixels_per_thread * thread_index).min(total_pixels);
+            let num_pixels = pixels_per_thread.min(total_pixels - start_index);
+
+            (0..num_pixels).as_table(dimensions).enumerate_2d_from_index(start_index).map(|(index, _)| {
+                let ray = local_camera.get_primary_ray(index);
+                local_scene.trace(&ray, 0).color
+            }).collect::<Vec<_>>()
+        })
+    }).collect::<Vec<_>>();
+    let thread_setup_end = time::precise_time_ns();
+
+    let thread_waiting_start = time::precise_time_ns();
+    let initial_coloring = initial_coloring_threads.into_iter().flat_map(|f| f.join().unwrap().into_iter()).collect::<Vec<_>>();
+    let thread_waiting_end = time::precise_time_ns();
+
+    // Collect the colored pixels back into the original table.
+    let pixel_combining_start = time::precise_time_ns();
+    for (pixel, color) in pixel_table.iter_mut().zip(initial_coloring.iter()) {
+        *pixel = color.clone();
+    }
+    let pixel_combining_end = time::precise_time_ns();
+    
+    // Edge Detection
+    let edge_detection_start = time::precise_time_ns();
+    let mut is_edge = Table::from_elem(dimensions, options.anti_aliasing == AntiAliasing::Full);
+    for (index, value) in is_edge.iter_mut().enumerate_2d() {
+        let (row, column) = index;
+        if options.anti_aliasing == AntiAliasing::EdgeDetect && row != 0 && column != 0 && row != height-1 && column != width-1 {
+            let ref p1 = pixel_table[(row - 1, column - 1)];
+            let ref p2 = pixel_table[(row - 1, column)];
+            let ref p3 = pixel_table[(row - 1, column + 1)];
+            let ref p4 = pixel_table[(row, column - 1)];
+            let ref p6 = pixel_table[(row, column + 1)];
+            let ref p7 = pixel_table[(row + 1, column - 1)];
+            let ref p8 = pixel_table[(row + 1, column)];
+            let ref p9 = pixel_table[(row + 1, column + 1)];
+
+            let r = calculate_gradient(p1.red, p2.red, p3.red, p4.red, p6.red, p7.red, p8.red, p9.red);
+            let g = calculate_gradient(p1.green, p2.green, p3.green, p4.green, p6.green, p7.green, p8.green, p9.green);
+            let b = calculate_gradient(p1.blue, p2.blue, p3.blue, p4.blue, p6.blue, p7.blue, p8.blue, p9.blue);
+
+            if (r + b + g) > 0.5 {
+                *value = true;
+            } else {
+                *value = false;
+            }
+        }
+    }
+    let edge_detection_end = time::precise_time_ns();
+
+    // Anti-aliasing
+    let anti_aliasing_start = time::precise_time_ns();
+    let sub_width = 3;
+    let sub_height = 3;
+    let sub_size = (sub_width * sub_height) as f32;
+    let mut sub_rays = Table::from_elem((sub_width, sub_height), Ray3D::new(Point3D::origin(), Direction3D::unit_x()));
+    for (index, value) in pixel_table.iter_mut().enumerate_2d() {
+        if is_edge[index] {
+            let mut pixel_color = ColorRGB::black().clone();
+            
+            camera.get_sub_rays(index, &mut sub_rays);
+            for sub_ray in &sub_rays {
+                let result = scene.trace(sub_ray, 0);
+                
+                pixel_color = ColorRGB::from_rgb(
+                    pixel_color.red + result.color.red / sub_size,
+                    pixel_color.green + result.color.green / sub_size,
+                    pixel_color.blue + result.color.blue / sub_size
+                );
+            }
+            *value = pixel_color;
+        }
+    }
+    let anti_aliasing_end = time::precise_time_ns();
+
+    // let elapsed = (initial_coloring_end - intital_coloring_start) / 1000000;
+    // println!("Initial Coloring: {}", elapsed);
+    if options.verbosity == Verbosity::Verbose {
+        let elapsed = (thread_setup_end - thread_setup_start) / 1000000;
+        println!("Thread Setup    : {}", elapsed);
+        let elapsed = (thread_waiting_end - thread_waiting_start) / 1000000;
+        println!("Thread Waiting  : {}", elapsed);
+        let elapsed = (pixel_combining_end - pixel_combining_start) / 1000000;
+        println!("Pixel Combining : {}", elapsed);
+        let elapsed = (edge_detection_end - edge_detection_start) / 1000000;
+        println!("Edge Detection  : {}", elapsed);
+        let elapsed = (anti_aliasing_end - anti_aliasing_start) / 1000000;
+        println!("Anti-aliasing   : {}", elapsed);
+    }
+
+    pixel_table
+}
+
+fn calculate_gradient(p1: f32, p2: f32, p3: f32, p4: f32, p6: f32, p7: f32, p8: f32, p9: f32) -> f32
+{
+    // The pixels hold unclamped radiance, so only compare the displayable
+    // range. Otherwise every pixel near a bright light would count as an edge.
+    let (p1, p2, p3, p4) = (p1.min(1.0), p2.min(1.0), p3.min(1.0), p4.min(1.0));
+    let (p6, p7, p8, p9) = (p6.min(1.0), p7.min(1.0), p8.min(1.0), p9.min(1.0));
+
+    let gx = (p3 + 2.0 * p6 + p9) - (p1 + 2.0 * p4 + p7);
+    let gy = (p1 + 2.0 * p2 + p3) - (p7 + 2.0 * p8 + p9);
+    (gx*gx + gy*gy).sqrt()
+}
@@ -0,0 +1,113 @@
+use std::f32;
+use std::sync::{Arc};
+
+use geometry::{Point3D, Direction3D, Ray3D, Aabb};
+use material::{Material};
+
+use super::hit::{Hit};
+use super::scene_object::{SceneObject, nearest_boundary};
+
+#[derive(Clone, Debug)]
+pub struct AxisAlignedBox {
+    bounds: Aabb,
+    material: Arc<Material>
+}
+
+impl AxisAlignedBox {
+    pub fn new(min: &Point3D, max: &Point3D, material: &Arc<Material>) -> AxisAlignedBox {
+        AxisAlignedBox {
+            bounds: Aabb::from_points(&[min.clone(), max.clone()]),
+            material: material.clone()
+        }
+    }
+
+    pub fn get_min(&self) -> &Point3D {
+        &self.bounds.min
+    }
+
+    pub fn get_max(&self) -> &Point3D {
+        &self.bounds.max
+    }
+
+    // Returns the axis of the face nearest to the point and whether it is the
+    // face on the maximum side.
+    fn get_nearest_face(&self, point: &Point3D) -> (usize, bool) {
+        let coordinates = [point.x, point.y, point.z];
+        let min = [self.bounds.min.x, self.bounds.min.y, self.bounds.min.z];
+        let max = [self.bounds.max.x, self.bounds.max.y, self.bounds.max.z];
+
+        let mut nearest = (0, false);
+        let mut nearest_distance = f32::INFINITY;
+        for axis in 0..3 {
+            let distance_to_min = (coordinates[axis] - min[axis]).abs();
+            let distance_to_max = (coordinates[axis] - max[axis]).abs();
+            if distance_to_min < nearest_distance {
+                nearest_distance = distance_to_min;
+                nearest = (axis, false);
+            }
+            if distance_to_max < nearest_distance {
+                nearest_distance = distance_to_max;
+                nearest = (axis, true);
+            }
+        }
+        nearest
+    }
+
+    fn get_normal(&self, point: &Point3D) -> Direction3D {
+        let (axis, is_max) = self.get_nearest_face(point);
+        let sign = if is_max { 1.0 } else { -1.0 };
+        match axis {
+            0 => Direction3D::from_xyz(sign, 0.0, 0.0),
+            1 => Direction3D::from_xyz(0.0, sign, 0.0),
+            _ => Direction3D::from_xyz(0.0, 0.0, sign)
+        }
+    }
+
+    // Each face is mapped to the unit square using the two axes it spans.
+    fn get_uv(&self, point: &Point3D) -> (f32, f32) {
+        let (axis, _) = self.get_nearest_face(point);
+        let size = self.bounds.size();
+        let relative = [
+            (point.x - self.bounds.min.x) / size.x,
+            (point.y - self.bounds.min.y) / size.y,
+            (point.z - self.bounds.min.z) / size.z
+        ];
+        match axis {
+            0 => (relative[2], relative[1]),
+            1 => (relative[0], relative[2]),
+            _ => (relative[0], relative[1])
+        }
+    }
+
+    // The directions in which `u` and `v` increase on the face.
+    fn get_tangents(&self, point: &Point3D) -> (&'static Direction3D, &'static Direction3D) {
+        match self.get_nearest_face(point).0 {
+            0 => (Direction3D::unit_z(), Direction3D::unit_y()),
+            1 => (Direction3D::unit_x(), Direction3D::unit_z()),
+            _ => (Direction3D::unit_x(), Direction3D::unit_y())
+        }
+    }
+}
+
+impl SceneObject for AxisAlignedBox {
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>> {
+        let interval = self.bounds.intersect_interval(ray, f32::NEG_INFINITY, f32::INFINITY)?;
+        let distance = nearest_boundary(ray, &[interval])?;
+
+        let point = ray.point(distance);
+        let mut hit = Hit::new(ray, distance, &self.get_normal(&point), &*self.material);
+        hit.uv = self.get_uv(&point);
+        let (tangent, bitangent) = self.get_tangents(&point);
+        hit.set_tangents(tangent, bitangent);
+        Some(hit)
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        Some(self.bounds.clone())
+    }
+
+    fn get_intervals(&self, ray: &Ray3D) -> Option<Vec<(f32, f32)>> {
+        let interval = self.bounds.intersect_interval(ray, f32::NEG_INFINITY, f32::INFINITY);
+        Some(interval.into_iter().collect())
+    }
+}
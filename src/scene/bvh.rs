@@ -0,0 +1,307 @@
+use std::f32;
+
+use geometry::{Point3D, Ray3D, Aabb};
+
+const BIN_COUNT: usize = 12;
+const MAX_LEAF_SIZE: usize = 4;
+const TRAVERSAL_COST: f32 = 1.0;
+const INTERSECTION_COST: f32 = 1.0;
+
+// A bounding volume hierarchy over a set of indexed bounding boxes, built with
+// the binned surface area heuristic. The hierarchy only stores indices, so the
+// caller decides what is tested when a ray reaches a leaf.
+#[derive(Debug)]
+pub struct Bvh {
+    nodes: Vec<BvhNode>,
+    indices: Vec<usize>
+}
+
+#[derive(Debug)]
+struct BvhNode {
+    bounds: Aabb,
+    kind: BvhNodeKind
+}
+
+#[derive(Debug)]
+enum BvhNodeKind {
+    Leaf {
+        start: usize,
+        count: usize
+    },
+    Interior {
+        left: usize,
+        right: usize
+    }
+}
+
+// What a candidate's intersection test returns, so that the nearest one can be
+// kept.
+pub trait HitDistance {
+    fn get_distance(&self) -> f32;
+}
+
+impl HitDistance for f32 {
+    fn get_distance(&self) -> f32 {
+        *self
+    }
+}
+
+struct BuildItem {
+    index: usize,
+    bounds: Aabb,
+    centroid: Point3D
+}
+
+impl Bvh {
+    pub fn new(items: &[(usize, Aabb)]) -> Bvh {
+        let mut build_items = items.iter().map(|&(index, ref bounds)| {
+            BuildItem {
+                index: index,
+                bounds: bounds.clone(),
+                centroid: bounds.centroid()
+            }
+        }).collect::<Vec<_>>();
+
+        let mut bvh = Bvh {
+            nodes: Vec::new(),
+            indices: Vec::with_capacity(items.len())
+        };
+        if !build_items.is_empty() {
+            bvh.build(&mut build_items);
+        }
+        bvh
+    }
+
+    // Finds the closest hit within the ray's range. `intersect` is called with
+    // the ray and the index of each candidate and returns its hit, if any. The
+    // ray is shortened to the nearest hit found so far, so that the candidates
+    // can reject farther hits early.
+    pub fn intersect<H: HitDistance, F: FnMut(&Ray3D, usize) -> Option<H>>(&self, ray: &Ray3D, mut intersect: F) -> Option<(usize, H)> {
+        if self.nodes.is_empty() {
+            return None;
+        }
+
+        let mut nearest: Option<(usize, H)> = None;
+        let mut nearest_ray = ray.clone();
+
+        let mut stack = Vec::with_capacity(64);
+        if self.nodes[0].bounds.intersect(&nearest_ray).is_some() {
+            stack.push(0);
+        }
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            match node.kind {
+                BvhNodeKind::Leaf {start, count} => {
+                    for &index in &self.indices[start..start + count] {
+                        if let Some(hit) = intersect(&nearest_ray, index) {
+                            let distance = hit.get_distance();
+                            if nearest.is_none() || distance < nearest_ray.t_max {
+                                nearest_ray.t_max = distance;
+                                nearest = Some((index, hit));
+                            }
+                        }
+                    }
+                },
+                BvhNodeKind::Interior {left, right} => {
+                    let left_hit = self.nodes[left].bounds.intersect(&nearest_ray);
+                    let right_hit = self.nodes[right].bounds.intersect(&nearest_ray);
+
+                    // Visit the nearer child first so that it can shrink the
+                    // search distance for the other one.
+                    match (left_hit, right_hit) {
+                        (Some(left_distance), Some(right_distance)) => {
+                            if left_distance <= right_distance {
+                                stack.push(right);
+                                stack.push(left);
+                            } else {
+                                stack.push(left);
+                                stack.push(right);
+                            }
+                        },
+                        (Some(_), None) => stack.push(left),
+                        (None, Some(_)) => stack.push(right),
+                        (None, None) => {}
+                    }
+                }
+            }
+        }
+
+        nearest
+    }
+
+    // Returns as soon as `is_hit` reports a hit for any candidate whose bounds
+    // the ray enters within its range.
+    pub fn any_hit<F: FnMut(usize) -> bool>(&self, ray: &Ray3D, mut is_hit: F) -> bool {
+        if self.nodes.is_empty() {
+            return false;
+        }
+
+        let mut stack = Vec::with_capacity(64);
+        stack.push(0);
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if node.bounds.intersect(ray).is_none() {
+                continue;
+            }
+
+            match node.kind {
+                BvhNodeKind::Leaf {start, count} => {
+                    for &index in &self.indices[start..start + count] {
+                        if is_hit(index) {
+                            return true;
+                        }
+                    }
+                },
+                BvhNodeKind::Interior {left, right} => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+
+        false
+    }
+
+    fn build(&mut self, items: &mut [BuildItem]) -> usize {
+        let count = items.len();
+        let bounds = items.iter().fold(Aabb::empty(), |bounds, item| Aabb::union(&bounds, &item.bounds));
+
+        let node_index = self.nodes.len();
+        self.nodes.push(BvhNode {
+            bounds: bounds.clone(),
+            kind: BvhNodeKind::Leaf {start: 0, count: 0}
+        });
+
+        let split = if count > 1 {
+            find_split(items, &bounds)
+        } else {
+            None
+        };
+
+        match split {
+            Some(middle) => {
+                let (left_items, right_items) = items.split_at_mut(middle);
+                let left = self.build(left_items);
+                let right = self.build(right_items);
+                self.nodes[node_index].kind = BvhNodeKind::Interior {left: left, right: right};
+            },
+            None => {
+                let leaf_start = self.indices.len();
+                for item in items.iter() {
+                    self.indices.push(item.index);
+                }
+                self.nodes[node_index].kind = BvhNodeKind::Leaf {start: leaf_start, count: count};
+            }
+        }
+
+        node_index
+    }
+}
+
+// Partitions `items` and returns the size of the left half, or `None` if the
+// items are better off in a single leaf.
+fn find_split(items: &mut [BuildItem], bounds: &Aabb) -> Option<usize> {
+    let count = items.len();
+    let centroid_bounds = items.iter().fold(Aabb::empty(), |bounds, item| Aabb::union(&bounds, &Aabb::new(&item.centroid, &item.centroid)));
+
+    let extents = [
+        centroid_bounds.max.x - centroid_bounds.min.x,
+        centroid_bounds.max.y - centroid_bounds.min.y,
+        centroid_bounds.max.z - centroid_bounds.min.z
+    ];
+    let axis = if extents[0] >= extents[1] && extents[0] >= extents[2] {
+        0
+    } else if extents[1] >= extents[2] {
+        1
+    } else {
+        2
+    };
+    let extent = extents[axis];
+    let axis_min = get_axis(&centroid_bounds.min, axis);
+
+    // All centroids coincide, so no split can separate them.
+    if extent <= 0.0 || extent.is_nan() {
+        return if count > MAX_LEAF_SIZE {
+            Some(count / 2)
+        } else {
+            None
+        };
+    }
+
+    let bin_of = |item: &BuildItem| -> usize {
+        let offset = (get_axis(&item.centroid, axis) - axis_min) / extent;
+        ((offset * BIN_COUNT as f32) as usize).min(BIN_COUNT - 1)
+    };
+
+    let mut bin_bounds: Vec<Aabb> = (0..BIN_COUNT).map(|_| Aabb::empty()).collect();
+    let mut bin_counts = [0usize; BIN_COUNT];
+    for item in items.iter() {
+        let bin = bin_of(item);
+        bin_bounds[bin] = Aabb::union(&bin_bounds[bin], &item.bounds);
+        bin_counts[bin] += 1;
+    }
+
+    // Evaluate the cost of splitting after each bin.
+    let parent_area = bounds.surface_area();
+    let mut best_cost = f32::INFINITY;
+    let mut best_bin = 0;
+    for split_bin in 0..BIN_COUNT - 1 {
+        let mut left_bounds = Aabb::empty();
+        let mut left_count = 0;
+        for bin in 0..split_bin + 1 {
+            left_bounds = Aabb::union(&left_bounds, &bin_bounds[bin]);
+            left_count += bin_counts[bin];
+        }
+
+        let mut right_bounds = Aabb::empty();
+        let mut right_count = 0;
+        for bin in split_bin + 1..BIN_COUNT {
+            right_bounds = Aabb::union(&right_bounds, &bin_bounds[bin]);
+            right_count += bin_counts[bin];
+        }
+
+        if left_count == 0 || right_count == 0 {
+            continue;
+        }
+
+        let cost = TRAVERSAL_COST + INTERSECTION_COST *
+            (left_bounds.surface_area() * left_count as f32 + right_bounds.surface_area() * right_count as f32) / parent_area;
+        if cost < best_cost {
+            best_cost = cost;
+            best_bin = split_bin;
+        }
+    }
+
+    let leaf_cost = INTERSECTION_COST * count as f32;
+    if count <= MAX_LEAF_SIZE && best_cost >= leaf_cost {
+        return None;
+    }
+    if best_cost == f32::INFINITY {
+        return Some(count / 2);
+    }
+
+    // Move every item in the bins left of the split to the front.
+    let mut middle = 0;
+    for index in 0..count {
+        if bin_of(&items[index]) <= best_bin {
+            items.swap(index, middle);
+            middle += 1;
+        }
+    }
+
+    if middle == 0 || middle == count {
+        Some(count / 2)
+    } else {
+        Some(middle)
+    }
+}
+
+fn get_axis(point: &Point3D, axis: usize) -> f32 {
+    match axis {
+        0 => point.x,
+        1 => point.y,
+        _ => point.z
+    }
+}
@@ -65,6 +65,21 @@ impl Camera {
     pub fn get_image_dimensions(&self) -> (usize, usize) {
         self.image_dimensions
     }
+
+    pub fn set_image_dimensions(&mut self, image_dimensions: (usize, usize)) {
+        let (image_width_usize, image_height_usize) = image_dimensions;
+        let image_width = image_width_usize as f32;
+        let image_height = image_height_usize as f32;
+
+        // Keep the vertical extent of the view plane and widen or narrow it to
+        // match the new aspect ratio.
+        let x_min = -self.y_max * image_width / image_height;
+
+        self.image_dimensions = image_dimensions;
+        self.x_min = x_min;
+        self.dx = -2.0 * x_min / image_width;
+        self.dy = 2.0 * self.y_max / image_height;
+    }
     
     pub fn get_primary_ray(&self, index: (usize, usize)) -> Ray3D {
         let (row, column) = index;
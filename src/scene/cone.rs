@@ -0,0 +1,179 @@
+use std::f32;
+use std::f32::consts::{PI};
+use std::sync::{Arc};
+
+use geometry::{EPSILON, Point3D, Vector3D, Direction3D, Ray3D, Aabb, Transform};
+use geometry::polynomial::{solve_quadratic};
+use material::{Material};
+
+use super::disk::{disk_bounds};
+use super::hit::{Hit};
+use super::scene_object::{SceneObject, nearest_boundary};
+
+// A finite cone between two points with a radius at each end, closed by a disk
+// at every end whose radius is not zero. A top radius of zero gives a pointed
+// cone.
+#[derive(Clone, Debug)]
+pub struct Cone {
+    base: Point3D,
+    top: Point3D,
+    base_radius: f32,
+    top_radius: f32,
+    height: f32,
+    frame: Transform,
+    inverse_frame: Transform,
+    material: Arc<Material>
+}
+
+impl Cone {
+    pub fn new(base: &Point3D, top: &Point3D, base_radius: f32, top_radius: f32, material: &Arc<Material>) -> Cone {
+        let frame = Transform::from_origin_axis(base, &Direction3D::between_points(base, top));
+        Cone {
+            base: base.clone(),
+            top: top.clone(),
+            base_radius: base_radius,
+            top_radius: top_radius,
+            height: Point3D::distance(base, top),
+            inverse_frame: frame.inverse(),
+            frame: frame,
+            material: material.clone()
+        }
+    }
+
+    pub fn get_base(&self) -> &Point3D {
+        &self.base
+    }
+
+    pub fn get_top(&self) -> &Point3D {
+        &self.top
+    }
+
+    // The radius of the cone at height `y` above the base.
+    fn get_radius_at(&self, y: f32) -> f32 {
+        self.base_radius + (self.top_radius - self.base_radius) * y / self.height
+    }
+
+    // Returns -1 or 1 if the local point is nearer to the base or top cap
+    // than to the side, or 0 if it is on the side.
+    fn get_nearest_part(&self, local: &Point3D) -> i32 {
+        let radial = (local.x * local.x + local.z * local.z).sqrt();
+        let side_distance = (radial - self.get_radius_at(local.y)).abs();
+        let base_distance = local.y.abs();
+        let top_distance = (local.y - self.height).abs();
+
+        if self.base_radius > 0.0 && base_distance < side_distance && base_distance <= top_distance {
+            -1
+        } else if self.top_radius > 0.0 && top_distance < side_distance {
+            1
+        } else {
+            0
+        }
+    }
+
+    fn get_normal(&self, point: &Point3D) -> Direction3D {
+        let local = self.inverse_frame.transform_point(point);
+        let local_normal = match self.get_nearest_part(&local) {
+            -1 => Vector3D::from_xyz(0.0, -1.0, 0.0),
+            1 => Vector3D::from_xyz(0.0, 1.0, 0.0),
+            _ => {
+                let slope = (self.top_radius - self.base_radius) / self.height;
+                Vector3D::from_xyz(local.x, -slope * self.get_radius_at(local.y), local.z)
+            }
+        };
+
+        self.frame.transform_vector(&local_normal).to_unit()
+    }
+
+    // On the side, `u` is the angle around the axis and `v` the height, both
+    // scaled to [0, 1]. The caps are mapped to the unit square.
+    fn get_uv(&self, point: &Point3D) -> (f32, f32) {
+        let local = self.inverse_frame.transform_point(point);
+        match self.get_nearest_part(&local) {
+            0 => (0.5 + local.z.atan2(local.x) / (2.0 * PI), local.y / self.height),
+            part => {
+                let radius = if part < 0 { self.base_radius } else { self.top_radius };
+                (0.5 + local.x / (2.0 * radius), 0.5 + local.z / (2.0 * radius))
+            }
+        }
+    }
+
+    // The directions in which `u` and `v` increase.
+    fn get_tangents(&self, point: &Point3D) -> (Vector3D, Vector3D) {
+        let local = self.inverse_frame.transform_point(point);
+        let (local_tangent, local_bitangent) = match self.get_nearest_part(&local) {
+            0 => (Vector3D::from_xyz(-local.z, 0.0, local.x), Vector3D::from_xyz(0.0, 1.0, 0.0)),
+            _ => (Vector3D::from_xyz(1.0, 0.0, 0.0), Vector3D::from_xyz(0.0, 0.0, 1.0))
+        };
+        (self.frame.transform_vector(&local_tangent), self.frame.transform_vector(&local_bitangent))
+    }
+}
+
+impl SceneObject for Cone {
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>> {
+        let intervals = self.get_intervals(ray)?;
+        let distance = nearest_boundary(ray, &intervals)?;
+
+        let point = ray.point(distance);
+        let mut hit = Hit::new(ray, distance, &self.get_normal(&point), &*self.material);
+        hit.uv = self.get_uv(&point);
+        let (tangent, bitangent) = self.get_tangents(&point);
+        hit.set_tangents(&tangent, &bitangent);
+        Some(hit)
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        let axis = Direction3D::between_points(&self.base, &self.top);
+        Some(Aabb::union(
+            &disk_bounds(&self.base, &axis, self.base_radius),
+            &disk_bounds(&self.top, &axis, self.top_radius)
+        ))
+    }
+
+    // The cone is convex, so a ray is inside it between the nearest and
+    // farthest of the points where it crosses the side or a cap.
+    fn get_intervals(&self, ray: &Ray3D) -> Option<Vec<(f32, f32)>> {
+        let (local_ray, _) = self.inverse_frame.transform_ray(ray);
+        let (ox, oy, oz) = (local_ray.origin.x as f64, local_ray.origin.y as f64, local_ray.origin.z as f64);
+        let (dx, dy, dz) = (local_ray.direction.x() as f64, local_ray.direction.y() as f64, local_ray.direction.z() as f64);
+        let height = self.height as f64;
+        let base_radius = self.base_radius as f64;
+        let slope = (self.top_radius - self.base_radius) as f64 / height;
+
+        let mut t_enter = f64::INFINITY;
+        let mut t_exit = f64::NEG_INFINITY;
+
+        // The side: x^2 + z^2 = (base_radius + slope * y)^2 between the caps.
+        let radius_at_origin = base_radius + slope * oy;
+        let a = dx * dx + dz * dz - slope * slope * dy * dy;
+        let b = 2.0 * (ox * dx + oz * dz - slope * dy * radius_at_origin);
+        let c = ox * ox + oz * oz - radius_at_origin * radius_at_origin;
+        if let Some((t1, t2)) = solve_quadratic(a, b, c) {
+            for &t in &[t1, t2] {
+                let y = oy + t * dy;
+                if y >= 0.0 && y <= height {
+                    t_enter = t_enter.min(t);
+                    t_exit = t_exit.max(t);
+                }
+            }
+        }
+
+        // The caps.
+        if dy.abs() > EPSILON as f64 {
+            for &(cap_y, cap_radius) in &[(0.0, base_radius), (height, self.top_radius as f64)] {
+                let t = (cap_y - oy) / dy;
+                let x = ox + t * dx;
+                let z = oz + t * dz;
+                if x * x + z * z <= cap_radius * cap_radius {
+                    t_enter = t_enter.min(t);
+                    t_exit = t_exit.max(t);
+                }
+            }
+        }
+
+        if t_enter <= t_exit {
+            Some(vec![(t_enter as f32, t_exit as f32)])
+        } else {
+            Some(Vec::new())
+        }
+    }
+}
@@ -0,0 +1,272 @@
+use std::cmp::{Ordering};
+
+use geometry::{Ray3D, Aabb};
+
+use super::hit::{Hit};
+use super::scene_object::{SceneObject};
+
+// How far, relative to the size of the object, a child's surface may be from
+// the boundary it produced and still be considered the same surface.
+const SURFACE_TOLERANCE: f32 = 1.0e-3;
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference
+}
+
+impl CsgOperation {
+    fn is_inside(&self, in_left: bool, in_right: bool) -> bool {
+        match *self {
+            CsgOperation::Union => in_left || in_right,
+            CsgOperation::Intersection => in_left && in_right,
+            CsgOperation::Difference => in_left && !in_right
+        }
+    }
+}
+
+// Combines two closed objects into one by union, intersection or difference.
+// The difference removes the right object from the left one. Both objects must
+// report their intervals, and the result does too, so nodes can be nested.
+pub struct Csg {
+    operation: CsgOperation,
+    left: Box<SceneObject + Sync + Send>,
+    right: Box<SceneObject + Sync + Send>,
+    tolerance: f32
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Box<SceneObject + Sync + Send>, right: Box<SceneObject + Sync + Send>) -> Csg {
+        let extent = match (left.get_bounds(), right.get_bounds()) {
+            (Some(left_bounds), Some(right_bounds)) => {
+                let size = Aabb::union(&left_bounds, &right_bounds).size();
+                size.x.max(size.y).max(size.z)
+            },
+            _ => 1.0
+        };
+
+        Csg {
+            operation: operation,
+            left: left,
+            right: right,
+            tolerance: SURFACE_TOLERANCE * extent.max(1.0)
+        }
+    }
+
+    pub fn get_operation(&self) -> CsgOperation {
+        self.operation
+    }
+
+    fn get_boundaries(&self, ray: &Ray3D) -> Option<Vec<(Boundary, Boundary)>> {
+        let left = self.left.get_intervals(ray)?;
+        let right = self.right.get_intervals(ray)?;
+        Some(combine_intervals(self.operation, &left, &right))
+    }
+}
+
+impl SceneObject for Csg {
+    // The hit on the child that produced the nearest boundary is found again by
+    // intersecting that child close to the boundary. Surfaces carved out by
+    // the right object of a difference face into it, so those hits are
+    // flipped.
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>> {
+        let boundaries = self.get_boundaries(ray)?;
+        let boundary = find_nearest_boundary(ray, &boundaries)?;
+
+        let surface_ray = Ray3D::with_range(
+            &ray.origin,
+            &ray.direction,
+            (boundary.distance - self.tolerance).max(ray.t_min),
+            (boundary.distance + self.tolerance).min(ray.t_max)
+        );
+        let (object, is_right) = if boundary.is_left {
+            (&self.left, false)
+        } else {
+            (&self.right, true)
+        };
+
+        object.intersect(&surface_ray).map(|mut hit| {
+            if is_right && self.operation == CsgOperation::Difference {
+                hit.flip();
+            }
+            hit
+        })
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        match self.operation {
+            CsgOperation::Union => match (self.left.get_bounds(), self.right.get_bounds()) {
+                (Some(left_bounds), Some(right_bounds)) => Some(Aabb::union(&left_bounds, &right_bounds)),
+                _ => None
+            },
+            CsgOperation::Intersection => match (self.left.get_bounds(), self.right.get_bounds()) {
+                (Some(left_bounds), Some(right_bounds)) => Some(Aabb::intersection(&left_bounds, &right_bounds)),
+                (Some(bounds), None) | (None, Some(bounds)) => Some(bounds),
+                (None, None) => None
+            },
+            CsgOperation::Difference => self.left.get_bounds()
+        }
+    }
+
+    fn get_intervals(&self, ray: &Ray3D) -> Option<Vec<(f32, f32)>> {
+        self.get_boundaries(ray).map(|boundaries| {
+            boundaries.iter().map(|(enter, exit)| (enter.distance, exit.distance)).collect()
+        })
+    }
+}
+
+// A distance where the ray enters or leaves a combination, and the child whose
+// surface it is on.
+#[derive(Clone, Debug)]
+struct Boundary {
+    distance: f32,
+    is_left: bool
+}
+
+fn find_nearest_boundary<'a>(ray: &Ray3D, boundaries: &'a [(Boundary, Boundary)]) -> Option<&'a Boundary> {
+    for (enter, exit) in boundaries {
+        if ray.is_in_range(enter.distance) {
+            return Some(enter);
+        }
+        if ray.is_in_range(exit.distance) {
+            return Some(exit);
+        }
+    }
+    None
+}
+
+// Walks through the boundaries of both children in order, tracking whether the
+// ray is inside each of them, and records where the combined inside starts and
+// ends.
+fn combine_intervals(operation: CsgOperation, left: &[(f32, f32)], right: &[(f32, f32)]) -> Vec<(Boundary, Boundary)> {
+    let mut boundaries = Vec::with_capacity(2 * (left.len() + right.len()));
+    for &(t_enter, t_exit) in left {
+        boundaries.push((t_enter, true));
+        boundaries.push((t_exit, true));
+    }
+    for &(t_enter, t_exit) in right {
+        boundaries.push((t_enter, false));
+        boundaries.push((t_exit, false));
+    }
+    boundaries.sort_by(|a, b| a.0.partial_cmp(&b.0).unwrap_or(Ordering::Equal));
+
+    let mut intervals = Vec::new();
+    let mut in_left = false;
+    let mut in_right = false;
+    let mut is_inside = false;
+    let mut start = Boundary {distance: 0.0, is_left: true};
+    for (t, is_left) in boundaries {
+        if is_left {
+            in_left = !in_left;
+        } else {
+            in_right = !in_right;
+        }
+
+        let was_inside = is_inside;
+        is_inside = operation.is_inside(in_left, in_right);
+        if is_inside && !was_inside {
+            start = Boundary {distance: t, is_left: is_left};
+        } else if !is_inside && was_inside {
+            intervals.push((start.clone(), Boundary {distance: t, is_left: is_left}));
+        }
+    }
+    intervals
+}
+
+#[cfg(test)]
+mod tests {
+    use geometry::{Point3D, Direction3D, Ray3D};
+    use material::{MaterialBuilder};
+    use scene::{SceneObject, Sphere, AxisAlignedBox, Hit};
+
+    use super::{Csg, CsgOperation};
+
+    fn sphere_at(z: f32) -> Box<SceneObject + Sync + Send> {
+        Box::new(Sphere::new(&Point3D::from_xyz(0.0, 0.0, z), 1.0, &MaterialBuilder::new().to_material()))
+    }
+
+    // Two unit spheres along the z axis, at `left_z` and `right_z`.
+    fn spheres(operation: CsgOperation, left_z: f32, right_z: f32) -> Csg {
+        Csg::new(operation, sphere_at(left_z), sphere_at(right_z))
+    }
+
+    fn ray_from(z: f32) -> Ray3D {
+        Ray3D::with_range(&Point3D::from_xyz(0.0, 0.0, z), Direction3D::unit_z(), 1.0e-4, f32::INFINITY)
+    }
+
+    fn assert_intervals(csg: &Csg, ray: &Ray3D, expected: &[(f32, f32)]) {
+        let intervals = csg.get_intervals(ray).unwrap();
+        assert_eq!(intervals.len(), expected.len(), "expected {:?}, got {:?}", expected, intervals);
+        for (&(enter, exit), &(expected_enter, expected_exit)) in intervals.iter().zip(expected) {
+            assert!((enter - expected_enter).abs() < 1.0e-4 && (exit - expected_exit).abs() < 1.0e-4, "expected {:?}, got {:?}", expected, intervals);
+        }
+    }
+
+    // Checks the distance of the hit and on which side of the surface the ray
+    // arrives, with the normal pointing along -z on the front.
+    fn assert_hit(actual: Option<Hit>, expected: f32, front_face: bool) {
+        let hit = actual.unwrap_or_else(|| panic!("expected {}, got no hit", expected));
+        assert!((hit.distance - expected).abs() < 1.0e-4, "expected {}, got {}", expected, hit.distance);
+        assert_eq!(hit.front_face, front_face);
+        let normal_z = if front_face { -1.0 } else { 1.0 };
+        assert!((hit.geometric_normal.z() - normal_z).abs() < 1.0e-4, "got normal {:?}", hit.geometric_normal);
+    }
+
+    #[test]
+    fn combines_overlapping_intervals() {
+        let ray = ray_from(0.0);
+        assert_intervals(&spheres(CsgOperation::Union, 5.0, 6.0), &ray, &[(4.0, 7.0)]);
+        assert_intervals(&spheres(CsgOperation::Intersection, 5.0, 6.0), &ray, &[(5.0, 6.0)]);
+        assert_intervals(&spheres(CsgOperation::Difference, 5.0, 6.0), &ray, &[(4.0, 5.0)]);
+        assert_intervals(&spheres(CsgOperation::Difference, 6.0, 5.0), &ray, &[(6.0, 7.0)]);
+    }
+
+    #[test]
+    fn keeps_separate_intervals_apart() {
+        let ray = ray_from(0.0);
+        assert_intervals(&spheres(CsgOperation::Union, 5.0, 9.0), &ray, &[(4.0, 6.0), (8.0, 10.0)]);
+        assert_intervals(&spheres(CsgOperation::Intersection, 5.0, 9.0), &ray, &[]);
+        assert_intervals(&spheres(CsgOperation::Difference, 5.0, 9.0), &ray, &[(4.0, 6.0)]);
+    }
+
+    #[test]
+    fn hits_the_nearest_surface_of_each_operation() {
+        let ray = ray_from(0.0);
+        assert_hit(spheres(CsgOperation::Union, 5.0, 6.0).intersect(&ray), 4.0, true);
+        assert_hit(spheres(CsgOperation::Intersection, 5.0, 6.0).intersect(&ray), 5.0, true);
+        assert_hit(spheres(CsgOperation::Difference, 5.0, 6.0).intersect(&ray), 4.0, true);
+        assert!(spheres(CsgOperation::Intersection, 5.0, 9.0).intersect(&ray).is_none());
+    }
+
+    #[test]
+    fn flips_the_carved_surface_of_a_difference() {
+        // The near cap of the left sphere is cut away, so the ray first meets
+        // the far side of the right one, which now faces it.
+        assert_hit(spheres(CsgOperation::Difference, 6.0, 5.0).intersect(&ray_from(0.0)), 6.0, true);
+
+        // From inside, the carved surface is seen from behind.
+        assert_hit(spheres(CsgOperation::Difference, 5.0, 6.0).intersect(&ray_from(4.5)), 0.5, false);
+    }
+
+    #[test]
+    fn hits_from_inside_the_combination() {
+        let ray = ray_from(5.5);
+        assert_hit(spheres(CsgOperation::Union, 5.0, 6.0).intersect(&ray), 1.5, false);
+        assert_hit(spheres(CsgOperation::Intersection, 5.0, 6.0).intersect(&ray), 0.5, false);
+    }
+
+    #[test]
+    fn carves_a_box_out_of_a_sphere() {
+        let cutter = AxisAlignedBox::new(&Point3D::from_xyz(-0.5, -0.5, 3.5), &Point3D::from_xyz(0.5, 0.5, 5.0), &MaterialBuilder::new().to_material());
+        let csg = Csg::new(CsgOperation::Difference, sphere_at(5.0), Box::new(cutter));
+
+        assert_intervals(&csg, &ray_from(0.0), &[(5.0, 6.0)]);
+        assert_hit(csg.intersect(&ray_from(0.0)), 5.0, true);
+
+        // Beside the box, the ray still meets the sphere.
+        let beside = Ray3D::new(&Point3D::from_xyz(0.0, 0.75, 0.0), Direction3D::unit_z());
+        let hit = csg.intersect(&beside).unwrap();
+        assert!(hit.front_face && hit.distance < 5.0, "got {} at {:?}", hit.distance, hit.point);
+    }
+}
@@ -0,0 +1,162 @@
+use std::f32;
+use std::f32::consts::{PI};
+use std::sync::{Arc};
+
+use geometry::{EPSILON, Point3D, Vector3D, Direction3D, Ray3D, Aabb, Transform};
+use geometry::polynomial::{solve_quadratic};
+use material::{Material};
+
+use super::disk::{disk_bounds};
+use super::hit::{Hit};
+use super::scene_object::{SceneObject, nearest_boundary};
+
+// A finite cylinder between two points, closed at both ends.
+#[derive(Clone, Debug)]
+pub struct Cylinder {
+    base: Point3D,
+    top: Point3D,
+    radius: f32,
+    height: f32,
+    frame: Transform,
+    inverse_frame: Transform,
+    material: Arc<Material>
+}
+
+impl Cylinder {
+    pub fn new(base: &Point3D, top: &Point3D, radius: f32, material: &Arc<Material>) -> Cylinder {
+        let frame = Transform::from_origin_axis(base, &Direction3D::between_points(base, top));
+        Cylinder {
+            base: base.clone(),
+            top: top.clone(),
+            radius: radius,
+            height: Point3D::distance(base, top),
+            inverse_frame: frame.inverse(),
+            frame: frame,
+            material: material.clone()
+        }
+    }
+
+    pub fn get_base(&self) -> &Point3D {
+        &self.base
+    }
+
+    pub fn get_top(&self) -> &Point3D {
+        &self.top
+    }
+
+    pub fn get_radius(&self) -> f32 {
+        self.radius
+    }
+
+    fn get_normal(&self, point: &Point3D) -> Direction3D {
+        let local = self.inverse_frame.transform_point(point);
+        let radial = (local.x * local.x + local.z * local.z).sqrt();
+
+        let side_distance = (radial - self.radius).abs();
+        let local_normal = if local.y.abs() < side_distance && local.y.abs() <= (local.y - self.height).abs() {
+            Vector3D::from_xyz(0.0, -1.0, 0.0)
+        } else if (local.y - self.height).abs() < side_distance {
+            Vector3D::from_xyz(0.0, 1.0, 0.0)
+        } else {
+            Vector3D::from_xyz(local.x, 0.0, local.z)
+        };
+
+        self.frame.transform_vector(&local_normal).to_unit()
+    }
+
+    // On the side, `u` is the angle around the axis and `v` the height, both
+    // scaled to [0, 1]. The caps are mapped to the unit square.
+    fn get_uv(&self, point: &Point3D) -> (f32, f32) {
+        let local = self.inverse_frame.transform_point(point);
+        let radial = (local.x * local.x + local.z * local.z).sqrt();
+
+        let side_distance = (radial - self.radius).abs();
+        if local.y.abs() < side_distance || (local.y - self.height).abs() < side_distance {
+            (0.5 + local.x / (2.0 * self.radius), 0.5 + local.z / (2.0 * self.radius))
+        } else {
+            (0.5 + local.z.atan2(local.x) / (2.0 * PI), local.y / self.height)
+        }
+    }
+
+    // The directions in which `u` and `v` increase.
+    fn get_tangents(&self, point: &Point3D) -> (Vector3D, Vector3D) {
+        let local = self.inverse_frame.transform_point(point);
+        let radial = (local.x * local.x + local.z * local.z).sqrt();
+
+        let side_distance = (radial - self.radius).abs();
+        let (local_tangent, local_bitangent) = if local.y.abs() < side_distance || (local.y - self.height).abs() < side_distance {
+            (Vector3D::from_xyz(1.0, 0.0, 0.0), Vector3D::from_xyz(0.0, 0.0, 1.0))
+        } else {
+            (Vector3D::from_xyz(-local.z, 0.0, local.x), Vector3D::from_xyz(0.0, 1.0, 0.0))
+        };
+        (self.frame.transform_vector(&local_tangent), self.frame.transform_vector(&local_bitangent))
+    }
+}
+
+impl SceneObject for Cylinder {
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>> {
+        let intervals = self.get_intervals(ray)?;
+        let distance = nearest_boundary(ray, &intervals)?;
+
+        let point = ray.point(distance);
+        let mut hit = Hit::new(ray, distance, &self.get_normal(&point), &*self.material);
+        hit.uv = self.get_uv(&point);
+        let (tangent, bitangent) = self.get_tangents(&point);
+        hit.set_tangents(&tangent, &bitangent);
+        Some(hit)
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        let axis = Direction3D::between_points(&self.base, &self.top);
+        Some(Aabb::union(
+            &disk_bounds(&self.base, &axis, self.radius),
+            &disk_bounds(&self.top, &axis, self.radius)
+        ))
+    }
+
+    // The cylinder is convex, so a ray is inside it between the nearest and
+    // farthest of the points where it crosses the side or a cap.
+    fn get_intervals(&self, ray: &Ray3D) -> Option<Vec<(f32, f32)>> {
+        let (local_ray, _) = self.inverse_frame.transform_ray(ray);
+        let (ox, oy, oz) = (local_ray.origin.x as f64, local_ray.origin.y as f64, local_ray.origin.z as f64);
+        let (dx, dy, dz) = (local_ray.direction.x() as f64, local_ray.direction.y() as f64, local_ray.direction.z() as f64);
+        let radius = self.radius as f64;
+        let height = self.height as f64;
+
+        let mut t_enter = f64::INFINITY;
+        let mut t_exit = f64::NEG_INFINITY;
+
+        // The side, between the two caps.
+        let a = dx * dx + dz * dz;
+        let b = 2.0 * (ox * dx + oz * dz);
+        let c = ox * ox + oz * oz - radius * radius;
+        if let Some((t1, t2)) = solve_quadratic(a, b, c) {
+            for &t in &[t1, t2] {
+                let y = oy + t * dy;
+                if y >= 0.0 && y <= height {
+                    t_enter = t_enter.min(t);
+                    t_exit = t_exit.max(t);
+                }
+            }
+        }
+
+        // The caps.
+        if dy.abs() > EPSILON as f64 {
+            for &cap_y in &[0.0, height] {
+                let t = (cap_y - oy) / dy;
+                let x = ox + t * dx;
+                let z = oz + t * dz;
+                if x * x + z * z <= radius * radius {
+                    t_enter = t_enter.min(t);
+                    t_exit = t_exit.max(t);
+                }
+            }
+        }
+
+        if t_enter <= t_exit {
+            Some(vec![(t_enter as f32, t_exit as f32)])
+        } else {
+            Some(Vec::new())
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use std::f32::consts::{PI};
+use std::sync::{Arc};
+
+use geometry::{EPSILON, Point3D, Vector3D, Direction3D, Ray3D, Aabb, Transform};
+use material::{Material};
+
+use super::hit::{Hit};
+use super::scene_object::{SceneObject};
+
+#[derive(Clone, Debug)]
+pub struct Disk {
+    center: Point3D,
+    normal: Direction3D,
+    radius: f32,
+    frame: Transform,
+    inverse_frame: Transform,
+    material: Arc<Material>
+}
+
+impl Disk {
+    pub fn new(center: &Point3D, normal: &Direction3D, radius: f32, material: &Arc<Material>) -> Disk {
+        let frame = Transform::from_origin_axis(center, normal);
+        Disk {
+            center: center.clone(),
+            normal: normal.clone(),
+            radius: radius,
+            inverse_frame: frame.inverse(),
+            frame: frame,
+            material: material.clone()
+        }
+    }
+
+    pub fn get_center(&self) -> &Point3D {
+        &self.center
+    }
+
+    pub fn get_radius(&self) -> f32 {
+        self.radius
+    }
+}
+
+impl SceneObject for Disk {
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>> {
+        let (local_ray, _) = self.inverse_frame.transform_ray(ray);
+        let origin = &local_ray.origin;
+        let direction = &local_ray.direction;
+
+        if direction.y().abs() < EPSILON {
+            return None;
+        }
+        let t = -origin.y / direction.y();
+        if !local_ray.is_in_range(t) {
+            return None;
+        }
+
+        let x = origin.x + t * direction.x();
+        let z = origin.z + t * direction.z();
+        if x * x + z * z > self.radius * self.radius {
+            return None;
+        }
+
+        // Polar coordinates: `u` is the angle around the normal and `v` the
+        // distance from the center, both scaled to [0, 1].
+        let mut hit = Hit::new(ray, t, &self.normal, &*self.material);
+        hit.uv = (0.5 + z.atan2(x) / (2.0 * PI), (x * x + z * z).sqrt() / self.radius);
+        hit.set_tangents(
+            &self.frame.transform_vector(&Vector3D::from_xyz(-z, 0.0, x)),
+            &self.frame.transform_vector(&Vector3D::from_xyz(x, 0.0, z))
+        );
+        Some(hit)
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        Some(disk_bounds(&self.center, &self.normal, self.radius))
+    }
+}
+
+// Returns the bounds of a disk, which are also the bounds of any surface of
+// revolution that lies between its end disks.
+pub fn disk_bounds(center: &Point3D, normal: &Direction3D, radius: f32) -> Aabb {
+    let extent = Vector3D::from_xyz(
+        radius * (1.0 - normal.x() * normal.x()).max(0.0).sqrt(),
+        radius * (1.0 - normal.y() * normal.y()).max(0.0).sqrt(),
+        radius * (1.0 - normal.z() * normal.z()).max(0.0).sqrt()
+    );
+    Aabb::new(&center.translate_vec(&-&extent), &center.translate_vec(&extent))
+}
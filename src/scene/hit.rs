@@ -0,0 +1,153 @@
+use color::{ColorRGB};
+use geometry::{Point3D, Vector3D, AsVector, Direction3D, Ray3D};
+use material::{Material};
+use texture::{Texture};
+
+use super::bvh::{HitDistance};
+
+// Tangents shorter than this after being made perpendicular to the normal are
+// replaced by an arbitrary one.
+const MIN_TANGENT_LENGTH: f32 = 1.0e-6;
+
+// Step in texture coordinates, and in scene units along the tangents, over
+// which the slope of a bump map is measured.
+const BUMP_DELTA: f32 = 1.0e-3;
+
+// Everything that shading needs to know about where a ray hit a surface.
+// Normals point out of the object; `front_face` tells whether the ray arrived
+// from that side.
+#[derive(Clone, Debug)]
+pub struct Hit<'a> {
+    pub distance: f32,
+    pub point: Point3D,
+
+    // The normal of the actual surface, and the one used for shading, which
+    // differs from it where vertex normals are interpolated.
+    pub geometric_normal: Direction3D,
+    pub shading_normal: Direction3D,
+
+    // The tangent and bitangent point the ways `u` and `v` increase. Together
+    // with the shading normal they form an orthonormal frame.
+    pub uv: (f32, f32),
+    pub tangent: Direction3D,
+    pub bitangent: Direction3D,
+
+    pub front_face: bool,
+    pub material: &'a Material,
+
+    // Replaces the color of the material, for meshes with vertex colors.
+    pub vertex_color: Option<ColorRGB>
+}
+
+impl<'a> Hit<'a> {
+    // The shading normal starts out as the geometric one, with an arbitrary
+    // tangent frame around it.
+    pub fn new(ray: &Ray3D, distance: f32, normal: &Direction3D, material: &'a Material) -> Hit<'a> {
+        let tangent = arbitrary_tangent(normal);
+        let bitangent = Vector3D::cross(&tangent, normal).to_unit();
+        Hit {
+            distance: distance,
+            point: ray.point(distance),
+            geometric_normal: normal.clone(),
+            shading_normal: normal.clone(),
+            uv: (0.0, 0.0),
+            tangent: tangent,
+            bitangent: bitangent,
+            front_face: Vector3D::dot(&ray.direction, normal) < 0.0,
+            material: material,
+            vertex_color: None
+        }
+    }
+
+    // Replaces the shading normal and turns the tangent frame to match it.
+    pub fn set_shading_normal(&mut self, normal: &Direction3D) {
+        self.shading_normal = normal.clone();
+        let tangent = self.tangent.clone();
+        let bitangent = self.bitangent.clone();
+        self.set_tangents(&tangent, &bitangent);
+    }
+
+    // Sets the tangent frame from the direction in which `u` increases, for
+    // surfaces where `v` increases a quarter turn counterclockwise from it
+    // when looking at the front, as on textures that are not mirrored.
+    pub fn set_tangent<T: AsVector>(&mut self, tangent: &T) {
+        let bitangent = Vector3D::cross(tangent, &self.shading_normal);
+        self.set_tangents(tangent, &bitangent);
+    }
+
+    // Sets the tangent frame from the directions in which `u` and `v`
+    // increase. The tangent is made perpendicular to the shading normal, and
+    // the bitangent only decides on which side of it `v` increases.
+    pub fn set_tangents<T: AsVector, U: AsVector>(&mut self, tangent: &T, bitangent: &U) {
+        let normal = &self.shading_normal;
+        let tangent = tangent.as_vector();
+        let perpendicular = tangent - normal * Vector3D::dot(normal, tangent);
+
+        self.tangent = if perpendicular.magnitude() > MIN_TANGENT_LENGTH {
+            perpendicular.to_unit()
+        } else {
+            arbitrary_tangent(normal)
+        };
+        let counterclockwise = Vector3D::cross(&self.tangent, normal).to_unit();
+        self.bitangent = if Vector3D::dot(&counterclockwise, bitangent) < 0.0 {
+            -&counterclockwise
+        } else {
+            counterclockwise
+        };
+    }
+
+    // Turns the hit inside out, for surfaces that bound the outside of an
+    // object, like the faces carved by a difference.
+    pub fn flip(&mut self) {
+        self.geometric_normal = -&self.geometric_normal;
+        self.shading_normal = -&self.shading_normal;
+        self.front_face = !self.front_face;
+    }
+
+    // Tilts the shading normal by a normal map, which holds tangent space
+    // normals, with red, green and blue along the tangent, the bitangent and
+    // the normal.
+    pub fn apply_normal_map(&mut self, texture: &Texture) {
+        let color = texture.get_color(self.uv, &self.point);
+        let normal = &self.tangent * (2.0 * color.red - 1.0) +
+            &self.bitangent * (2.0 * color.green - 1.0) +
+            &self.shading_normal * (2.0 * color.blue - 1.0);
+        if normal.magnitude() > 0.0 {
+            self.set_shading_normal(&normal.to_unit());
+        }
+    }
+
+    // Tilts the shading normal by the slope of a height map, multiplied by
+    // `scale`.
+    pub fn apply_bump_map(&mut self, texture: &Texture, scale: f32) {
+        let (u, v) = self.uv;
+        let height = intensity(&texture.get_color(self.uv, &self.point));
+        let height_u = intensity(&texture.get_color((u + BUMP_DELTA, v), &self.point.translate_dist(&self.tangent, BUMP_DELTA)));
+        let height_v = intensity(&texture.get_color((u, v + BUMP_DELTA), &self.point.translate_dist(&self.bitangent, BUMP_DELTA)));
+
+        let scale = scale / BUMP_DELTA;
+        let normal = &self.shading_normal -
+            &self.tangent * ((height_u - height) * scale) -
+            &self.bitangent * ((height_v - height) * scale);
+        self.set_shading_normal(&normal.to_unit());
+    }
+}
+
+impl<'a> HitDistance for Hit<'a> {
+    fn get_distance(&self) -> f32 {
+        self.distance
+    }
+}
+
+fn intensity(color: &ColorRGB) -> f32 {
+    (color.red + color.green + color.blue) / 3.0
+}
+
+fn arbitrary_tangent(normal: &Direction3D) -> Direction3D {
+    let helper = if normal.x().abs() < 0.9 {
+        Direction3D::unit_x()
+    } else {
+        Direction3D::unit_z()
+    };
+    Vector3D::cross(helper, normal).to_unit()
+}
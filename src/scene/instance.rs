@@ -0,0 +1,105 @@
+use std::sync::{Arc};
+
+use geometry::{Ray3D, Aabb, Transform};
+
+use super::hit::{Hit};
+use super::scene_object::{SceneObject};
+
+// Places a shared object in the scene with its own transform. Rays are moved
+// into the object's space instead of copying the object, so the same mesh can
+// be placed many times.
+pub struct Instance {
+    object: Arc<SceneObject + Sync + Send>,
+    transform: Transform,
+    inverse_transform: Transform
+}
+
+impl Instance {
+    // `transform` maps the object's coordinates to scene coordinates.
+    pub fn new(object: Arc<SceneObject + Sync + Send>, transform: &Transform) -> Instance {
+        Instance {
+            object: object,
+            transform: transform.clone(),
+            inverse_transform: transform.inverse()
+        }
+    }
+
+    pub fn get_transform(&self) -> &Transform {
+        &self.transform
+    }
+}
+
+impl SceneObject for Instance {
+    // The object is hit in its own space, and the hit is moved back into the
+    // scene. Texture coordinates stay those of the object.
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>> {
+        let (object_ray, scale) = self.inverse_transform.transform_ray(ray);
+        self.object.intersect(&object_ray).map(|object_hit| {
+            let distance = object_hit.distance / scale;
+            let mut hit = Hit::new(ray, distance, &self.transform.transform_normal(&object_hit.geometric_normal), object_hit.material);
+            hit.uv = object_hit.uv;
+            hit.set_shading_normal(&self.transform.transform_normal(&object_hit.shading_normal));
+            hit.set_tangents(
+                &self.transform.transform_vector(&object_hit.tangent),
+                &self.transform.transform_vector(&object_hit.bitangent)
+            );
+            hit.front_face = object_hit.front_face;
+            hit.vertex_color = object_hit.vertex_color;
+            hit
+        })
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        self.object.get_bounds().map(|bounds| self.transform.transform_bounds(&bounds))
+    }
+
+    fn get_intervals(&self, ray: &Ray3D) -> Option<Vec<(f32, f32)>> {
+        let (object_ray, scale) = self.inverse_transform.transform_ray(ray);
+        self.object.get_intervals(&object_ray).map(|intervals| {
+            intervals.iter().map(|&(t_enter, t_exit)| (t_enter / scale, t_exit / scale)).collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc};
+
+    use geometry::{Point3D, Vector3D, Direction3D, Ray3D, Transform};
+    use material::{MaterialBuilder};
+    use scene::{SceneObject, Sphere};
+
+    use super::{Instance};
+
+    // A unit sphere at the origin, stretched along z and moved to z = 10.
+    fn stretched_sphere() -> Instance {
+        let sphere = Arc::new(Sphere::new(Point3D::origin(), 1.0, &MaterialBuilder::new().to_material()));
+        let transform = Transform::scale(2.0, 2.0, 3.0).then(&Transform::translation(&Vector3D::from_xyz(0.0, 0.0, 10.0)));
+        Instance::new(sphere, &transform)
+    }
+
+    #[test]
+    fn reports_hits_in_scene_space() {
+        let instance = stretched_sphere();
+        let ray = Ray3D::new(Point3D::origin(), Direction3D::unit_z());
+        let hit = instance.intersect(&ray).unwrap();
+        assert!((hit.distance - 7.0).abs() < 1.0e-4, "expected 7, got {}", hit.distance);
+        assert!((hit.point.z - 7.0).abs() < 1.0e-4, "got {:?}", hit.point);
+        assert!((hit.geometric_normal.z() + 1.0).abs() < 1.0e-4 && hit.front_face, "got normal {:?}", hit.geometric_normal);
+
+        let intervals = instance.get_intervals(&ray).unwrap();
+        assert!(intervals.len() == 1 && (intervals[0].0 - 7.0).abs() < 1.0e-4 && (intervals[0].1 - 13.0).abs() < 1.0e-4, "got {:?}", intervals);
+    }
+
+    #[test]
+    fn keeps_the_range_of_the_ray_in_scene_space() {
+        let instance = stretched_sphere();
+        let ray = Ray3D::with_range(Point3D::origin(), Direction3D::unit_z(), 0.0, 6.0);
+        assert!(instance.intersect(&ray).is_none());
+
+        // Across the stretched side the sphere is two units wide.
+        let ray = Ray3D::new(&Point3D::from_xyz(-5.0, 0.0, 10.0), Direction3D::unit_x());
+        let hit = instance.intersect(&ray).unwrap();
+        assert!((hit.distance - 3.0).abs() < 1.0e-4, "expected 3, got {}", hit.distance);
+    }
+}
@@ -1,21 +1,48 @@
+use std::f32;
 use std::vec::{Vec};
 
-use geometry::{Vector3D, Ray3D};
+use geometry::{Vector3D, Ray3D, Aabb};
 use color::{ColorRGB};
+use mesh::{Mesh};
+
+use self::bvh::{Bvh};
 
 pub use self::scene_object::{SceneObject};
+pub use self::hit::{Hit};
 pub use self::scene_light::{SceneLight};
 pub use self::plane::{Plane};
 pub use self::sphere::{Sphere};
+pub use self::triangle::{Triangle};
+pub use self::triangle_mesh::{TriangleMesh};
+pub use self::instance::{Instance};
+pub use self::axis_aligned_box::{AxisAlignedBox};
+pub use self::cylinder::{Cylinder};
+pub use self::cone::{Cone};
+pub use self::disk::{Disk};
+pub use self::torus::{Torus};
+pub use self::csg::{Csg, CsgOperation};
 pub use self::camera::{Camera};
 
+mod bvh;
 mod scene_object;
+mod hit;
 mod scene_light;
 mod plane;
 mod sphere;
+mod triangle;
+mod triangle_mesh;
+mod instance;
+mod axis_aligned_box;
+mod cylinder;
+mod cone;
+mod disk;
+mod torus;
+mod csg;
 mod camera;
 
-const BIAS: f32 = 1.0e-4;
+// Secondary rays ignore hits nearer than this to their origin, so that they do
+// not hit the surface they start from.
+const MIN_DISTANCE: f32 = 1.0e-4;
 
 pub struct Scene {
     background_color: ColorRGB,
@@ -23,7 +50,10 @@ pub struct Scene {
     max_ray_depth: u32,
     
     items: Vec<InternalObject>,
-    lights: Vec<InternalLight>
+    lights: Vec<InternalLight>,
+
+    bvh: Option<Bvh>,
+    unbounded_items: Vec<usize>
 }
 
 impl Scene {
@@ -33,52 +63,141 @@ impl Scene {
             refractive_index: refractive_index,
             max_ray_depth: max_ray_depth,
             items: Vec::new(),
-            lights: Vec::new()
+            lights: Vec::new(),
+            bvh: None,
+            unbounded_items: Vec::new()
         }
     }
     
+    pub fn get_max_ray_depth(&self) -> u32 {
+        self.max_ray_depth
+    }
+
+    pub fn set_max_ray_depth(&mut self, max_ray_depth: u32) {
+        self.max_ray_depth = max_ray_depth;
+    }
+    
     pub fn add_light_source(&mut self, light: Box<SceneLight>) {
         let index = self.items.len();
         self.items.push(InternalObject {
             index: index,
-            object: light.clone(), 
-            is_light: true
+            object: light.clone()
         });
         self.lights.push(InternalLight {
             index: index,
             light: light
         });
+        self.bvh = None;
     }
     
     pub fn add_object(&mut self, object: Box<SceneObject+Sync+Send>) {
         let index = self.items.len();
         self.items.push(InternalObject {
             index: index,
-            object: object, 
-            is_light: false
+            object: object
         });
+        self.bvh = None;
     }
 
-    pub fn trace(&self, ray: &Ray3D, depth: u32) -> TraceResult {
+    // Adds the mesh as a single object with its own bounding volume hierarchy.
+    // Faces without a material use the default material.
+    pub fn add_mesh(&mut self, mesh: &Mesh) {
+        if mesh.faces.is_empty() {
+            return;
+        }
+        self.add_object(Box::new(TriangleMesh::new(mesh)));
+    }
 
-        // Find the nearest object that the ray intersects.
-        let mut optional_nearest: Option<(&InternalObject, f32)> = None;
-        for current_item in &self.items {
-            let optional_intersection = current_item.object.intersect(ray);
-            if let Some(current_distance) = optional_intersection {
-                match optional_nearest {
-                    Some((_, nearest_distance)) => {
-                        if current_distance < nearest_distance {
-                            optional_nearest = Some((current_item, current_distance));
-                        }
-                    },
-                    None => {
-                        optional_nearest = Some((current_item, current_distance));
-                    }
+    // Builds the bounding volume hierarchy over every bounded object. Until
+    // this is called, or after objects are added, every ray is tested against
+    // every object.
+    pub fn finalize(&mut self) {
+        let mut bounded_items = Vec::new();
+        self.unbounded_items.clear();
+
+        for item in &self.items {
+            match item.object.get_bounds() {
+                Some(bounds) => bounded_items.push((item.index, bounds)),
+                None => self.unbounded_items.push(item.index)
+            }
+        }
+
+        self.bvh = Some(Bvh::new(&bounded_items));
+    }
+
+    // Returns the bounds of every object in the scene, or `None` if the scene
+    // contains an unbounded object.
+    pub fn get_bounds(&self) -> Option<Aabb> {
+        let mut bounds = Aabb::empty();
+        for item in &self.items {
+            match item.object.get_bounds() {
+                Some(item_bounds) => bounds = Aabb::union(&bounds, &item_bounds),
+                None => return None
+            }
+        }
+        Some(bounds)
+    }
+
+    pub fn is_finalized(&self) -> bool {
+        self.bvh.is_some()
+    }
+
+    // Checks whether any object blocks the ray within its range. This stops at
+    // the first blocker found instead of looking for the nearest one.
+    pub fn is_occluded(&self, ray: &Ray3D) -> bool {
+        self.is_blocked(ray, None)
+    }
+
+    // The objects that are not in the bounding volume hierarchy, which is
+    // every object until it has been built.
+    fn get_linear_items<'a>(&'a self) -> Box<Iterator<Item = usize> + 'a> {
+        match self.bvh {
+            Some(_) => Box::new(self.unbounded_items.iter().cloned()),
+            None => Box::new(0..self.items.len())
+        }
+    }
+
+    // Finds the object that the ray hits first. Every hit shortens the ray, so
+    // that objects farther away are rejected early.
+    fn find_nearest(&self, ray: &Ray3D) -> Option<Hit<'_>> {
+        let mut nearest: Option<(usize, Hit)> = None;
+        let mut nearest_ray = ray.clone();
+
+        for index in self.get_linear_items() {
+            if let Some(hit) = self.items[index].object.intersect(&nearest_ray) {
+                if nearest.is_none() || hit.distance < nearest_ray.t_max {
+                    nearest_ray.t_max = hit.distance;
+                    nearest = Some((index, hit));
                 }
             }
         }
 
+        if let Some(ref bvh) = self.bvh {
+            let items = &self.items;
+            if let Some(hit) = bvh.intersect(&nearest_ray, |ray, index| items[index].object.intersect(ray)) {
+                nearest = Some(hit);
+            }
+        }
+
+        nearest.map(|(_, hit)| hit)
+    }
+
+    // Checks whether any object other than `ignored_index` blocks the ray
+    // within its range.
+    fn is_blocked(&self, ray: &Ray3D, ignored_index: Option<usize>) -> bool {
+        let is_hit = |index: usize| {
+            Some(index) != ignored_index && self.items[index].object.intersect(ray).is_some()
+        };
+
+        self.get_linear_items().any(&is_hit) ||
+        self.bvh.as_ref().is_some_and(|bvh| bvh.any_hit(ray, &is_hit))
+    }
+
+    pub fn trace(&self, ray: &Ray3D, depth: u32) -> TraceResult {
+
+        // Find the nearest object that the ray intersects.
+        let optional_nearest = self.find_nearest(ray);
+
         // If the ray doesn't hit any objects, return the background color.
         if optional_nearest.is_none() {
             return TraceResult {
@@ -88,114 +207,48 @@ impl Scene {
         }
         
         // Get the information about the nearest intersection.
-        let (nearest_item, nearest_distance) = optional_nearest.unwrap();
+        let mut hit = optional_nearest.unwrap();
+        let nearest_distance = hit.distance;
 
-        // Get the point where the ray intersects the object.
-        let point = ray.point(nearest_distance);
+        // Shading uses the normal tilted by the normal and bump maps of the
+        // material, while the side of the surface the ray came from is still
+        // decided by the geometry.
+        let material = hit.material;
+        material.apply_normal_maps(&mut hit);
+        let point = &hit.point;
+        let to_viewer = -&ray.direction;
 
-        // If the ray intersects a light source, simply return the color of the light.
-        if nearest_item.is_light {
-            return TraceResult {
-                color: nearest_item.object.get_material(&point).color, 
-                distance: nearest_distance
-            };
-        }
-
-        // Get the surface normal and color at the intersection point.
-        let normal = nearest_item.object.get_normal(&point);
-        let surface_material = nearest_item.object.get_material(&point);
-        
         // Calculate the color at the intersection point.
-        let mut total_ray_color = ColorRGB::black().clone();
-        
-        if depth < self.max_ray_depth {
-            // TODO: Add Fresnel effects (?)
-
-            // Calculate the color from the reflected ray.
-            let reflection = surface_material.reflection;
-            if reflection > 0.0 {
-                let reflected_direction = (&ray.direction - &normal * 2.0 * Vector3D::dot(&ray.direction, &normal)).to_unit();
-                let nearby_point = point.translate_dist(&reflected_direction, BIAS);
-                let reflected_result = self.trace(&Ray3D::new(&nearby_point, &reflected_direction), depth + 1);
-                total_ray_color = &total_ray_color + &reflected_result.color * reflection * &surface_material.color;
-            }
+        let mut total_ray_color = material.get_emission(&hit);
 
-            // Calculate the color from the refracted ray.
-            let refraction = surface_material.refraction;
-            if refraction > 0.0 {
-                let n;
-                let cos_i;
-                if Vector3D::dot(&ray.direction, &normal) > 0.0 {
-                    // Internal refraction
-                    n = surface_material.refractive_index / self.refractive_index;
-                    cos_i = -Vector3D::dot(&ray.direction, &(-&normal));
+        // Calculate the color from the rays the material scatters.
+        if depth < self.max_ray_depth {
+            for scattered in material.sample(&hit, &ray.direction, self.refractive_index) {
+                let scattered_ray = Ray3D::with_range(point, &scattered.direction, MIN_DISTANCE, f32::INFINITY);
+                let scattered_result = self.trace(&scattered_ray, depth + 1);
+                let scattered_color = if scattered.is_transmitted {
+                    &scattered_result.color * &material.get_transparency(&hit, scattered_result.distance) * &scattered.weight
                 } else {
-                    // External refraction
-                    n = self.refractive_index / surface_material.refractive_index;
-                    cos_i = -Vector3D::dot(&ray.direction, &normal);
-                }
-
-                let cos2_t = 1.0 - n * n * (1.0 - cos_i * cos_i);
-                if cos2_t > 0.0 {
-                    let refracted_direction = (&ray.direction * n + &normal * (n * cos_i - (cos2_t).sqrt())).to_unit();
-                    let nearby_point = point.translate_dist(&refracted_direction, BIAS);
-                    let refracted_result = self.trace(&Ray3D::new(&nearby_point, &refracted_direction), depth + 1);
-
-                    // Beer's Law
-                    let absorbance = &surface_material.color * (0.15 * -refracted_result.distance);
-                    let transparency = ColorRGB::from_rgb(absorbance.red.exp(), absorbance.green.exp(), absorbance.blue.exp());
-                    total_ray_color = &total_ray_color + &refracted_result.color * transparency;
-                }
+                    &scattered_result.color * &scattered.weight
+                };
+                total_ray_color = &total_ray_color + &scattered_color;
             }
         }
-        
+
         // Calculate the color from each light in the scene.
         for light_item in &self.lights {
             let light = &light_item.light;
-            let light_color = light.get_material(&point).color;
-            let vector_to_light = Vector3D::between_points(&point, light.get_center());
+            let vector_to_light = Vector3D::between_points(point, light.get_center());
             let distance_to_light = vector_to_light.magnitude();
             let direction_to_light = vector_to_light.to_unit();
 
-            // Calculate the shading from the light.
-            let mut shade: f32 = 1.0;
-            let nearby_point = point.translate_dist(&direction_to_light, BIAS);
-            let shadow_ray = Ray3D::new(&nearby_point, &direction_to_light);
-            for shadow_item in &self.items {
-                if shadow_item.index != light_item.index {
-                    let shadow_result = shadow_item.object.intersect(&shadow_ray);
-                    if let Some(distance) = shadow_result {
-                        if distance < distance_to_light {
-                            shade = 0.0;
-                            break;
-                        }
-                    }
-                }
-            }
-
-            if shade != 0.0 {
-                // Calculate the diffusive lighting from the light.
-                let diffuse = surface_material.diffuse;
-                if diffuse > 0.0 {
-                    let percentage_of_light = Vector3D::dot(&normal, &direction_to_light);
-                    if percentage_of_light > 0.0 {
-                        total_ray_color = &total_ray_color + (&light_color * &surface_material.color) * (shade * diffuse * percentage_of_light);
-                    }
-                }
-
-                // Calculate the specular lighting from the light.
-                let specular = surface_material.specular;
-                let shininess = surface_material.shininess;
-                if specular > 0.0 && shininess > 0 {
-                    let reflected_direction = (&direction_to_light - &normal * 2.0 * Vector3D::dot(&direction_to_light, &normal)).to_unit();
-                    let percentage_of_light = Vector3D::dot(&ray.direction, &reflected_direction);
-                    if percentage_of_light > 0.0 {
-                        total_ray_color = &total_ray_color + &light_color * (shade * specular * percentage_of_light.powi(shininess as i32));
-                    }
-                }
+            // Lights that are blocked cast a shadow.
+            let shadow_ray = Ray3D::with_range(point, &direction_to_light, MIN_DISTANCE, distance_to_light);
+            if !self.is_blocked(&shadow_ray, Some(light_item.index)) {
+                total_ray_color = &total_ray_color + &material.evaluate(&hit, &to_viewer, &direction_to_light, light.get_color());
             }
         }
-        
+
         TraceResult {
             color: total_ray_color,
             distance: nearest_distance
@@ -205,8 +258,7 @@ impl Scene {
 
 struct InternalObject {
     pub index: usize,
-    pub object: Box<SceneObject+Sync+Send>,
-    pub is_light: bool
+    pub object: Box<SceneObject+Sync+Send>
 }
 
 struct InternalLight {
@@ -1,29 +1,41 @@
-use geometry::{EPSILON, Point3D, Vector3D, AsVector, Direction3D, Ray3D};
+use std::sync::{Arc};
+
+use geometry::{EPSILON, Point3D, Vector3D, AsVector, Direction3D, Ray3D, Aabb};
 use material::{Material};
 
+use super::hit::{Hit};
 use super::scene_object::{SceneObject};
 
 #[derive(Clone, Debug)]
 pub struct Plane {
     origin: Point3D,
     normal: Direction3D,
-    material: Material
+    u_axis: Direction3D,
+    v_axis: Direction3D,
+    material: Arc<Material>
 }
 
 impl Plane {
-    pub fn from_origin_normal(origin: &Point3D, normal: &Direction3D, material: &Material) -> Plane {
+    pub fn from_origin_normal(origin: &Point3D, normal: &Direction3D, material: &Arc<Material>) -> Plane {
+        let (u_axis, v_axis) = get_axes(normal);
         Plane {
             origin: origin.clone(),
             normal: normal.clone(),
+            u_axis: u_axis,
+            v_axis: v_axis,
             material: material.clone()
         }
     }
     
-    pub fn from_d_vector<T: AsVector>(d: f32, vector: &T, material: &Material) -> Plane {
+    pub fn from_d_vector<T: AsVector>(d: f32, vector: &T, material: &Arc<Material>) -> Plane {
         let v = vector.as_vector();        
+        let normal = Direction3D::from_vector(v);
+        let (u_axis, v_axis) = get_axes(&normal);
         Plane {
             origin: Point3D::from_vector(&(v * (-d / Vector3D::dot(v, v)))),
-            normal: Direction3D::from_vector(v),
+            normal: normal,
+            u_axis: u_axis,
+            v_axis: v_axis,
             material: material.clone()
         }
     }
@@ -38,25 +50,41 @@ impl Plane {
 }
 
 impl SceneObject for Plane {
-    fn intersect(&self, ray: &Ray3D) -> Option<f32> {
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>> {
         let denominator = Vector3D::dot(&ray.direction, &self.normal);
         if denominator.abs() < EPSILON {
             return None;
         }
 
         let t = Vector3D::dot(&Vector3D::between_points(&ray.origin, &self.origin), &self.normal) / denominator;
-        if t < 0.0 {
+        if !ray.is_in_range(t) {
             return None;
         }
 
-        Some(t)
-    }
-    
-    fn get_normal(&self, _: &Point3D) -> Direction3D {
-        self.normal.clone()
+        // Texture coordinates are measured in scene units from the origin of
+        // the plane, so textures repeat every unit.
+        let offset = Vector3D::between_points(&self.origin, &ray.point(t));
+        let mut hit = Hit::new(ray, t, &self.normal, &*self.material);
+        hit.uv = (Vector3D::dot(&offset, &self.u_axis), Vector3D::dot(&offset, &self.v_axis));
+        hit.set_tangent(&self.u_axis);
+        Some(hit)
     }
-    
-    fn get_material(&self, _: &Point3D) -> Material {
-        self.material.clone()
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        None
     }
 }
+
+// Chooses the directions of `u` and `v` on a plane so that textures appear
+// upright: `v` follows the y axis, or the z axis on planes that face up or
+// down, and `u` runs to the right when looking at the front of the plane.
+fn get_axes(normal: &Direction3D) -> (Direction3D, Direction3D) {
+    let up = if normal.y().abs() < 0.9 {
+        Direction3D::unit_y()
+    } else {
+        Direction3D::unit_z()
+    };
+    let v_axis = (up - normal * Vector3D::dot(up, normal)).to_unit();
+    let u_axis = Vector3D::cross(normal, &v_axis).to_unit();
+    (u_axis, v_axis)
+}
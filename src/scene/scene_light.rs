@@ -1,19 +1,25 @@
-use geometry::{Point3D, Direction3D, Ray3D};
+use std::sync::{Arc};
+
+use geometry::{Point3D, Ray3D, Aabb};
 use color::{ColorRGB};
-use material::{Material};
+use material::{Material, Emission};
 
+use super::hit::{Hit};
 use super::scene_object::{SceneObject};
 use super::sphere::{Sphere};
 
 #[derive(Clone, Debug)]
 pub struct SceneLight {
-    sphere: Sphere
+    sphere: Sphere,
+    color: ColorRGB
 }
 
 impl SceneLight {
     pub fn new(center: &Point3D, radius: f32, color: &ColorRGB) -> SceneLight {
+        let material: Arc<Material> = Arc::new(Emission::new(color));
         SceneLight {
-            sphere: Sphere::new(center, radius, &Material::new(color))
+            sphere: Sphere::new(center, radius, &material),
+            color: color.clone()
         }
     }
     
@@ -24,18 +30,18 @@ impl SceneLight {
     pub fn get_radius(&self) -> f32 {
         self.sphere.get_radius()
     }
+
+    pub fn get_color(&self) -> &ColorRGB {
+        &self.color
+    }
 }
 
 impl SceneObject for SceneLight {
-    fn intersect(&self, ray: &Ray3D) -> Option<f32> {
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>> {
         self.sphere.intersect(ray)
     }
-    
-    fn get_normal(&self, point: &Point3D) -> Direction3D {
-        self.sphere.get_normal(point)
-    }
-    
-    fn get_material(&self, point: &Point3D) -> Material {
-        self.sphere.get_material(point)
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        self.sphere.get_bounds()
     }
 }
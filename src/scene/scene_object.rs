@@ -1,8 +1,35 @@
-use geometry::{Point3D, Direction3D, Ray3D};
-use material::{Material};
+use geometry::{Ray3D, Aabb};
+
+use super::hit::{Hit};
 
 pub trait SceneObject {
-    fn intersect(&self, ray: &Ray3D) -> Option<f32>;
-    fn get_normal(&self, point: &Point3D) -> Direction3D;
-    fn get_material(&self, point: &Point3D) -> Material;
+    // Returns the nearest hit within the ray's range.
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>>;
+
+    // Returns `None` for objects that extend forever, like planes. Those are
+    // tested against every ray instead of being placed in the scene's
+    // bounding volume hierarchy.
+    fn get_bounds(&self) -> Option<Aabb>;
+
+    // Returns the spans of the ray that are inside the object as sorted,
+    // disjoint (entry, exit) distances, including spans behind the ray
+    // origin. Only closed objects have an inside; the others return `None` and
+    // cannot be used in constructive solid geometry.
+    fn get_intervals(&self, _: &Ray3D) -> Option<Vec<(f32, f32)>> {
+        None
+    }
+}
+
+// Returns the distance to the first boundary within the ray's range, which is
+// where the ray enters or, if it starts inside, leaves the object.
+pub fn nearest_boundary(ray: &Ray3D, intervals: &[(f32, f32)]) -> Option<f32> {
+    for &(t_enter, t_exit) in intervals {
+        if ray.is_in_range(t_enter) {
+            return Some(t_enter);
+        }
+        if ray.is_in_range(t_exit) {
+            return Some(t_exit);
+        }
+    }
+    None
 }
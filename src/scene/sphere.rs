@@ -1,22 +1,25 @@
-use geometry::{Point3D, Vector3D, Direction3D, Ray3D};
+use std::f32::consts::{PI};
+use std::sync::{Arc};
+
+use geometry::{Point3D, Vector3D, Direction3D, Ray3D, Aabb};
+use geometry::polynomial::{solve_quadratic};
 use material::{Material};
 
-use super::scene_object::{SceneObject};
+use super::hit::{Hit};
+use super::scene_object::{SceneObject, nearest_boundary};
 
 #[derive(Clone, Debug)]
 pub struct Sphere {
     center: Point3D,
     radius: f32,
-    radius_squared: f32,
-    material: Material
+    material: Arc<Material>
 }
 
 impl Sphere {
-    pub fn new(center: &Point3D, radius: f32, material: &Material) -> Sphere {
+    pub fn new(center: &Point3D, radius: f32, material: &Arc<Material>) -> Sphere {
         Sphere {
             center: center.clone(),
             radius: radius,
-            radius_squared: radius * radius,
             material: material.clone()
         }
     }
@@ -28,35 +31,124 @@ impl Sphere {
     pub fn get_radius(&self) -> f32 {
         self.radius
     }
+
+    pub fn get_material(&self) -> &Material {
+        &*self.material
+    }
+
+    // Distances along the whole line of the ray to where it crosses the
+    // sphere, in ascending order.
+    fn get_roots(&self, ray: &Ray3D) -> Option<(f32, f32)> {
+        // Computed in double precision, since `c` cancels badly for large
+        // spheres like ground planes.
+        let ox = ray.origin.x as f64 - self.center.x as f64;
+        let oy = ray.origin.y as f64 - self.center.y as f64;
+        let oz = ray.origin.z as f64 - self.center.z as f64;
+        let (dx, dy, dz) = (ray.direction.x() as f64, ray.direction.y() as f64, ray.direction.z() as f64);
+        let radius = self.radius as f64;
+
+        let b = 2.0 * (ox * dx + oy * dy + oz * dz);
+        let c = ox * ox + oy * oy + oz * oz - radius * radius;
+        solve_quadratic(1.0, b, c).map(|(t1, t2)| (t1 as f32, t2 as f32))
+    }
 }
 
 impl SceneObject for Sphere {
-    fn intersect(&self, ray: &Ray3D) -> Option<f32> {
-        let sphere_to_ray = Vector3D::between_points(&ray.origin, &self.center);
-        let b = Vector3D::dot(&sphere_to_ray, &ray.direction);
-        if b < 0.0 {
-            return None;
-        }
+    // The nearer root is where the ray enters the sphere and the farther one
+    // where it leaves, so a ray starting inside hits the farther one.
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>> {
+        let roots = self.get_roots(ray)?;
+        let distance = nearest_boundary(ray, &[roots])?;
 
-        let d_squared = Vector3D::dot(&sphere_to_ray, &sphere_to_ray) - b * b;
-        if d_squared > self.radius_squared {
-            return None;
-        }
+        // Spherical coordinates: `u` is the angle around the y axis and `v`
+        // runs from the bottom pole to the top one, both scaled to [0, 1].
+        let normal = Direction3D::between_points(&self.center, &ray.point(distance));
+        let mut hit = Hit::new(ray, distance, &normal, &*self.material);
+        hit.uv = (
+            0.5 + normal.z().atan2(normal.x()) / (2.0 * PI),
+            0.5 + normal.y().clamp(-1.0, 1.0).asin() / PI
+        );
+        hit.set_tangent(&Vector3D::from_xyz(-normal.z(), 0.0, normal.x()));
+        Some(hit)
+    }
 
-        let c = (self.radius_squared - d_squared).sqrt();
-        let mut t = b - c;
-        if t < 0.0 {
-            t = b + c;
+    fn get_bounds(&self) -> Option<Aabb> {
+        Some(Aabb::new(
+            &Point3D::from_xyz(self.center.x - self.radius, self.center.y - self.radius, self.center.z - self.radius),
+            &Point3D::from_xyz(self.center.x + self.radius, self.center.y + self.radius, self.center.z + self.radius)
+        ))
+    }
+
+    fn get_intervals(&self, ray: &Ray3D) -> Option<Vec<(f32, f32)>> {
+        Some(self.get_roots(ray).into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geometry::{Point3D, Direction3D, Ray3D};
+    use material::{MaterialBuilder};
+    use scene::{SceneObject, Hit};
+
+    use super::{Sphere};
+
+    fn unit_sphere_at(z: f32) -> Sphere {
+        Sphere::new(&Point3D::from_xyz(0.0, 0.0, z), 1.0, &MaterialBuilder::new().to_material())
+    }
+
+    fn assert_near(actual: Option<Hit>, expected: f32) {
+        match actual {
+            Some(hit) => assert!((hit.distance - expected).abs() < 1.0e-5, "expected {}, got {}", expected, hit.distance),
+            None => panic!("expected {}, got no hit", expected)
         }
+    }
 
-        Some(t)
+    #[test]
+    fn hits_the_near_side_from_outside() {
+        let ray = Ray3D::new(Point3D::origin(), Direction3D::unit_z());
+        assert_near(unit_sphere_at(5.0).intersect(&ray), 4.0);
     }
-    
-    fn get_normal(&self, point: &Point3D) -> Direction3D {
-        Direction3D::between_points(&self.center, point)
+
+    #[test]
+    fn hits_the_far_side_from_inside_before_the_center() {
+        let ray = Ray3D::new(&Point3D::from_xyz(0.0, 0.0, 4.5), Direction3D::unit_z());
+        assert_near(unit_sphere_at(5.0).intersect(&ray), 1.5);
     }
-    
-    fn get_material(&self, _: &Point3D) -> Material {
-        self.material.clone()
+
+    #[test]
+    fn hits_the_far_side_from_inside_past_the_center() {
+        let ray = Ray3D::new(&Point3D::from_xyz(0.0, 0.0, 5.5), Direction3D::unit_z());
+        assert_near(unit_sphere_at(5.0).intersect(&ray), 0.5);
+    }
+
+    #[test]
+    fn hits_a_tangent_ray_once() {
+        let ray = Ray3D::new(&Point3D::from_xyz(1.0, 0.0, 0.0), Direction3D::unit_z());
+        assert_near(unit_sphere_at(5.0).intersect(&ray), 5.0);
+    }
+
+    #[test]
+    fn misses_a_sphere_behind_the_origin() {
+        let ray = Ray3D::new(Point3D::origin(), Direction3D::unit_z());
+        assert!(unit_sphere_at(-5.0).intersect(&ray).is_none());
+    }
+
+    #[test]
+    fn misses_a_ray_that_passes_beside_it() {
+        let ray = Ray3D::new(&Point3D::from_xyz(1.5, 0.0, 0.0), Direction3D::unit_z());
+        assert!(unit_sphere_at(5.0).intersect(&ray).is_none());
+    }
+
+    #[test]
+    fn skips_hits_nearer_than_the_minimum_distance() {
+        // A ray leaving the surface from inside must not hit its own origin.
+        let ray = Ray3D::with_range(&Point3D::from_xyz(0.0, 0.0, 4.0), Direction3D::unit_z(), 1.0e-4, f32::INFINITY);
+        assert_near(unit_sphere_at(5.0).intersect(&ray), 2.0);
+    }
+
+    #[test]
+    fn misses_hits_beyond_the_maximum_distance() {
+        let ray = Ray3D::with_range(Point3D::origin(), Direction3D::unit_z(), 0.0, 3.0);
+        assert!(unit_sphere_at(5.0).intersect(&ray).is_none());
     }
 }
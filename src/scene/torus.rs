@@ -0,0 +1,199 @@
+use std::f32::consts::{PI};
+use std::sync::{Arc};
+
+use geometry::{Point3D, Vector3D, Direction3D, Ray3D, Aabb, Transform};
+use geometry::polynomial::{solve_quadratic, solve_quartic};
+use material::{Material};
+
+use super::hit::{Hit};
+use super::scene_object::{SceneObject, nearest_boundary};
+
+// A ring around `axis` through `center`. `major_radius` is the distance from
+// the center to the middle of the tube and `minor_radius` the radius of the
+// tube.
+#[derive(Clone, Debug)]
+pub struct Torus {
+    center: Point3D,
+    axis: Direction3D,
+    major_radius: f32,
+    minor_radius: f32,
+    frame: Transform,
+    inverse_frame: Transform,
+    material: Arc<Material>
+}
+
+impl Torus {
+    pub fn new(center: &Point3D, axis: &Direction3D, major_radius: f32, minor_radius: f32, material: &Arc<Material>) -> Torus {
+        let frame = Transform::from_origin_axis(center, axis);
+        Torus {
+            center: center.clone(),
+            axis: axis.clone(),
+            major_radius: major_radius,
+            minor_radius: minor_radius,
+            inverse_frame: frame.inverse(),
+            frame: frame,
+            material: material.clone()
+        }
+    }
+
+    pub fn get_center(&self) -> &Point3D {
+        &self.center
+    }
+
+    pub fn get_major_radius(&self) -> f32 {
+        self.major_radius
+    }
+
+    pub fn get_minor_radius(&self) -> f32 {
+        self.minor_radius
+    }
+
+    fn get_normal(&self, point: &Point3D) -> Direction3D {
+        let local = self.inverse_frame.transform_point(point);
+
+        // The normal points away from the nearest point on the tube's center
+        // circle.
+        let radial = (local.x * local.x + local.z * local.z).sqrt();
+        let local_normal = if radial > 0.0 {
+            let scale = self.major_radius / radial;
+            Vector3D::from_xyz(local.x - local.x * scale, local.y, local.z - local.z * scale)
+        } else {
+            Vector3D::from_xyz(0.0, local.y, 0.0)
+        };
+
+        self.frame.transform_vector(&local_normal).to_unit()
+    }
+
+    // `u` is the angle around the axis and `v` the angle around the tube,
+    // both scaled to [0, 1].
+    fn get_uv(&self, point: &Point3D) -> (f32, f32) {
+        let local = self.inverse_frame.transform_point(point);
+        let radial = (local.x * local.x + local.z * local.z).sqrt();
+        (
+            0.5 + local.z.atan2(local.x) / (2.0 * PI),
+            0.5 + local.y.atan2(radial - self.major_radius) / (2.0 * PI)
+        )
+    }
+
+    fn get_tangent(&self, point: &Point3D) -> Vector3D {
+        let local = self.inverse_frame.transform_point(point);
+        self.frame.transform_vector(&Vector3D::from_xyz(-local.z, 0.0, local.x))
+    }
+}
+
+impl SceneObject for Torus {
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>> {
+        let intervals = self.get_intervals(ray)?;
+        let distance = nearest_boundary(ray, &intervals)?;
+
+        let point = ray.point(distance);
+        let mut hit = Hit::new(ray, distance, &self.get_normal(&point), &*self.material);
+        hit.uv = self.get_uv(&point);
+        hit.set_tangent(&self.get_tangent(&point));
+        Some(hit)
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        let extent = |component: f32| self.major_radius * (1.0 - component * component).max(0.0).sqrt() + self.minor_radius;
+        let extent = Vector3D::from_xyz(extent(self.axis.x()), extent(self.axis.y()), extent(self.axis.z()));
+        Some(Aabb::new(&self.center.translate_vec(&-&extent), &self.center.translate_vec(&extent)))
+    }
+
+    // The roots come in pairs where the ray enters and leaves the tube. An
+    // unpaired root is a tangent point and is dropped.
+    fn get_intervals(&self, ray: &Ray3D) -> Option<Vec<(f32, f32)>> {
+        let (local_ray, _) = self.inverse_frame.transform_ray(ray);
+        let (mut ox, mut oy, mut oz) = (local_ray.origin.x as f64, local_ray.origin.y as f64, local_ray.origin.z as f64);
+        let (dx, dy, dz) = (local_ray.direction.x() as f64, local_ray.direction.y() as f64, local_ray.direction.z() as f64);
+        let major_squared = (self.major_radius as f64).powi(2);
+        let minor_squared = (self.minor_radius as f64).powi(2);
+
+        // Reject rays that miss the bounding sphere, and start the others at
+        // the sphere so that the quartic is solved close to the roots, where
+        // it is far better conditioned.
+        let bounding_radius = (self.major_radius + self.minor_radius) as f64;
+        let f = ox * dx + oy * dy + oz * dz;
+        let (sphere_near, sphere_far) = match solve_quadratic(1.0, 2.0 * f, ox * ox + oy * oy + oz * oz - bounding_radius * bounding_radius) {
+            Some(roots) => roots,
+            None => return Some(Vec::new())
+        };
+        if sphere_far < 0.0 {
+            return Some(Vec::new());
+        }
+        let offset = sphere_near.max(0.0);
+        ox += offset * dx;
+        oy += offset * dy;
+        oz += offset * dz;
+
+        // Substituting the ray into (|p|^2 + R^2 - r^2)^2 = 4R^2(x^2 + z^2).
+        let f = ox * dx + oy * dy + oz * dz;
+        let e = ox * ox + oy * oy + oz * oz + major_squared - minor_squared;
+        let roots = solve_quartic(
+            1.0,
+            4.0 * f,
+            2.0 * e + 4.0 * f * f - 4.0 * major_squared * (dx * dx + dz * dz),
+            4.0 * f * e - 8.0 * major_squared * (ox * dx + oz * dz),
+            e * e - 4.0 * major_squared * (ox * ox + oz * oz)
+        );
+
+        let intervals = roots.chunks(2)
+            .filter(|pair| pair.len() == 2)
+            .map(|pair| ((pair[0] + offset) as f32, (pair[1] + offset) as f32))
+            .collect();
+        Some(intervals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use geometry::{Point3D, Direction3D, Ray3D};
+    use material::{MaterialBuilder};
+    use scene::{SceneObject, Hit};
+
+    use super::{Torus};
+
+    // A ring around the z axis with its tube crossing the x axis at 1.5 and
+    // 2.5.
+    fn ring() -> Torus {
+        Torus::new(Point3D::origin(), Direction3D::unit_z(), 2.0, 0.5, &MaterialBuilder::new().to_material())
+    }
+
+    fn assert_near(actual: Option<Hit>, expected: f32) {
+        match actual {
+            Some(hit) => assert!((hit.distance - expected).abs() < 1.0e-4, "expected {}, got {}", expected, hit.distance),
+            None => panic!("expected {}, got no hit", expected)
+        }
+    }
+
+    #[test]
+    fn misses_a_ray_along_the_axis_through_the_hole() {
+        let ray = Ray3D::new(&Point3D::from_xyz(0.0, 0.0, -5.0), Direction3D::unit_z());
+        assert!(ring().intersect(&ray).is_none());
+        assert_eq!(ring().get_intervals(&ray), Some(Vec::new()));
+    }
+
+    #[test]
+    fn hits_the_tube_parallel_to_the_axis() {
+        let ray = Ray3D::new(&Point3D::from_xyz(2.0, 0.0, -5.0), Direction3D::unit_z());
+        assert_near(ring().intersect(&ray), 4.5);
+    }
+
+    #[test]
+    fn crosses_the_tube_twice_through_the_center() {
+        let ray = Ray3D::new(&Point3D::from_xyz(-5.0, 0.0, 0.0), Direction3D::unit_x());
+        assert_near(ring().intersect(&ray), 2.5);
+
+        let intervals = ring().get_intervals(&ray).unwrap();
+        let expected = [(2.5, 3.5), (6.5, 7.5)];
+        assert_eq!(intervals.len(), expected.len());
+        for (&(enter, exit), &(expected_enter, expected_exit)) in intervals.iter().zip(&expected) {
+            assert!((enter - expected_enter).abs() < 1.0e-4 && (exit - expected_exit).abs() < 1.0e-4, "got {:?}", intervals);
+        }
+    }
+
+    #[test]
+    fn misses_a_ray_outside_the_ring() {
+        let ray = Ray3D::new(&Point3D::from_xyz(3.0, 0.0, -5.0), Direction3D::unit_z());
+        assert!(ring().intersect(&ray).is_none());
+    }
+}
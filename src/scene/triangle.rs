@@ -0,0 +1,170 @@
+use std::sync::{Arc};
+
+use color::{ColorRGB};
+use geometry::{EPSILON, Point3D, Vector3D, Direction3D, Ray3D, Aabb};
+use material::{Material};
+
+use super::hit::{Hit};
+use super::scene_object::{SceneObject};
+
+#[derive(Clone, Debug)]
+pub struct Triangle {
+    vertices: [Point3D; 3],
+    edge1: Vector3D,
+    edge2: Vector3D,
+    normal: Direction3D,
+    vertex_normals: Option<[Direction3D; 3]>,
+    vertex_uvs: Option<[(f32, f32); 3]>,
+    vertex_colors: Option<[ColorRGB; 3]>,
+    material: Arc<Material>
+}
+
+impl Triangle {
+    // The face normal is (vertex1 - vertex0) x (vertex2 - vertex0), so the
+    // winding order decides which side is lit.
+    pub fn new(vertex0: &Point3D, vertex1: &Point3D, vertex2: &Point3D, material: &Arc<Material>) -> Triangle {
+        let edge1 = Vector3D::between_points(vertex0, vertex1);
+        let edge2 = Vector3D::between_points(vertex0, vertex2);
+        let normal = Vector3D::cross(&edge1, &edge2).to_unit();
+
+        Triangle {
+            vertices: [vertex0.clone(), vertex1.clone(), vertex2.clone()],
+            edge1: edge1,
+            edge2: edge2,
+            normal: normal,
+            vertex_normals: None,
+            vertex_uvs: None,
+            vertex_colors: None,
+            material: material.clone()
+        }
+    }
+
+    // Normals at each vertex, interpolated across the face for smooth shading.
+    pub fn set_vertex_normals(&mut self, normals: &[Direction3D; 3]) {
+        self.vertex_normals = Some(normals.clone());
+    }
+
+    // Texture coordinates at each vertex, interpolated across the face.
+    pub fn set_vertex_uvs(&mut self, uvs: &[(f32, f32); 3]) {
+        self.vertex_uvs = Some(*uvs);
+    }
+
+    // Colors at each vertex, interpolated across the face. They replace the
+    // color of the material.
+    pub fn set_vertex_colors(&mut self, colors: &[ColorRGB; 3]) {
+        self.vertex_colors = Some(colors.clone());
+    }
+
+    pub fn get_vertices(&self) -> &[Point3D; 3] {
+        &self.vertices
+    }
+
+    pub fn get_face_normal(&self) -> &Direction3D {
+        &self.normal
+    }
+
+    // Returns the weights of the three vertices for a point on the triangle's
+    // plane. The weights sum to one.
+    pub fn get_barycentric(&self, point: &Point3D) -> (f32, f32, f32) {
+        barycentric(&self.vertices[0], &self.edge1, &self.edge2, point)
+    }
+}
+
+impl SceneObject for Triangle {
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>> {
+        let distance = intersect_triangle(ray, &self.vertices[0], &self.edge1, &self.edge2)?;
+
+        let mut hit = Hit::new(ray, distance, &self.normal, &*self.material);
+        let (w0, w1, w2) = self.get_barycentric(&hit.point);
+
+        // Without texture coordinates, `u` and `v` are the weights of the
+        // second and third vertex.
+        let uvs = self.vertex_uvs.unwrap_or([(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]);
+        hit.uv = (
+            w0 * uvs[0].0 + w1 * uvs[1].0 + w2 * uvs[2].0,
+            w0 * uvs[0].1 + w1 * uvs[1].1 + w2 * uvs[2].1
+        );
+        let (tangent, bitangent) = uv_derivatives(&self.edge1, &self.edge2, &uvs);
+        hit.set_tangents(&tangent, &bitangent);
+
+        if let Some(ref normals) = self.vertex_normals {
+            let normal = &normals[0] * w0 + &normals[1] * w1 + &normals[2] * w2;
+            hit.set_shading_normal(&normal.to_unit());
+        }
+        if let Some(ref colors) = self.vertex_colors {
+            hit.vertex_color = Some(&colors[0] * w0 + &colors[1] * w1 + &colors[2] * w2);
+        }
+        Some(hit)
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        Some(Aabb::from_points(&self.vertices))
+    }
+}
+
+// Möller–Trumbore intersection of a ray with the triangle spanned by `edge1`
+// and `edge2` from `vertex0`, within the ray's range.
+pub fn intersect_triangle(ray: &Ray3D, vertex0: &Point3D, edge1: &Vector3D, edge2: &Vector3D) -> Option<f32> {
+    let p = Vector3D::cross(&ray.direction, edge2);
+    let determinant = Vector3D::dot(edge1, &p);
+    if determinant.abs() < EPSILON {
+        return None;
+    }
+    let inverse_determinant = 1.0 / determinant;
+
+    let s = Vector3D::between_points(vertex0, &ray.origin);
+    let u = Vector3D::dot(&s, &p) * inverse_determinant;
+    if !(0.0..=1.0).contains(&u) {
+        return None;
+    }
+
+    let q = Vector3D::cross(&s, edge1);
+    let v = Vector3D::dot(&ray.direction, &q) * inverse_determinant;
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    let t = Vector3D::dot(edge2, &q) * inverse_determinant;
+    if !ray.is_in_range(t) {
+        return None;
+    }
+
+    Some(t)
+}
+
+// The directions in which `u` and `v` increase across the triangle spanned by
+// `edge1` and `edge2` with the given vertex texture coordinates. Falls back to
+// the edges when the texture coordinates do not span an area.
+pub fn uv_derivatives(edge1: &Vector3D, edge2: &Vector3D, uvs: &[(f32, f32); 3]) -> (Vector3D, Vector3D) {
+    let (du1, dv1) = (uvs[1].0 - uvs[0].0, uvs[1].1 - uvs[0].1);
+    let (du2, dv2) = (uvs[2].0 - uvs[0].0, uvs[2].1 - uvs[0].1);
+    let determinant = du1 * dv2 - du2 * dv1;
+    if determinant.abs() < EPSILON {
+        return (edge1.clone(), edge2.clone());
+    }
+    let inverse_determinant = 1.0 / determinant;
+    (
+        (edge1 * dv2 - edge2 * dv1) * inverse_determinant,
+        (edge2 * du1 - edge1 * du2) * inverse_determinant
+    )
+}
+
+// Barycentric weights of a point on the plane of the triangle spanned by
+// `edge1` and `edge2` from `vertex0`.
+pub fn barycentric(vertex0: &Point3D, edge1: &Vector3D, edge2: &Vector3D, point: &Point3D) -> (f32, f32, f32) {
+    let to_point = Vector3D::between_points(vertex0, point);
+    let d00 = Vector3D::dot(edge1, edge1);
+    let d01 = Vector3D::dot(edge1, edge2);
+    let d11 = Vector3D::dot(edge2, edge2);
+    let d20 = Vector3D::dot(&to_point, edge1);
+    let d21 = Vector3D::dot(&to_point, edge2);
+
+    let denominator = d00 * d11 - d01 * d01;
+    if denominator.abs() < EPSILON {
+        return (1.0, 0.0, 0.0);
+    }
+
+    let v = (d11 * d20 - d01 * d21) / denominator;
+    let w = (d00 * d21 - d01 * d20) / denominator;
+    (1.0 - v - w, v, w)
+}
@@ -0,0 +1,118 @@
+use std::sync::{Arc};
+
+use color::{ColorRGB};
+use geometry::{Point3D, Vector3D, Direction3D, Ray3D, Aabb};
+use material::{Material, MaterialBuilder};
+use mesh::{Mesh, MeshFace};
+
+use super::bvh::{Bvh};
+use super::hit::{Hit};
+use super::scene_object::{SceneObject};
+use super::triangle::{intersect_triangle, barycentric, uv_derivatives};
+
+// A triangle mesh stored as shared vertex buffers with its own bounding volume
+// hierarchy over the faces, so that it is a single object in the scene.
+#[derive(Debug)]
+pub struct TriangleMesh {
+    positions: Vec<Point3D>,
+    normals: Vec<Direction3D>,
+    uvs: Vec<(f32, f32)>,
+    colors: Vec<ColorRGB>,
+    faces: Vec<MeshFace>,
+    materials: Vec<Arc<Material>>,
+    default_material: Arc<Material>,
+    bounds: Aabb,
+    bvh: Bvh
+}
+
+// The geometry of one face, as used by the intersection code.
+struct FaceGeometry<'a> {
+    vertex0: &'a Point3D,
+    edge1: Vector3D,
+    edge2: Vector3D
+}
+
+impl TriangleMesh {
+    pub fn new(mesh: &Mesh) -> TriangleMesh {
+        let face_bounds = mesh.faces.iter().enumerate().map(|(index, face)| {
+            (index, Aabb::from_points(&[
+                mesh.positions[face.positions[0]].clone(),
+                mesh.positions[face.positions[1]].clone(),
+                mesh.positions[face.positions[2]].clone()
+            ]))
+        }).collect::<Vec<_>>();
+
+        TriangleMesh {
+            positions: mesh.positions.clone(),
+            normals: mesh.normals.clone(),
+            uvs: mesh.uvs.clone(),
+            colors: mesh.colors.clone(),
+            faces: mesh.faces.clone(),
+            materials: mesh.materials.clone(),
+            default_material: MaterialBuilder::new().to_material(),
+            bounds: mesh.get_bounds(),
+            bvh: Bvh::new(&face_bounds)
+        }
+    }
+
+    pub fn get_face_count(&self) -> usize {
+        self.faces.len()
+    }
+
+    fn get_face_geometry<'a>(&'a self, face: &MeshFace) -> FaceGeometry<'a> {
+        let vertex0 = &self.positions[face.positions[0]];
+        FaceGeometry {
+            vertex0: vertex0,
+            edge1: Vector3D::between_points(vertex0, &self.positions[face.positions[1]]),
+            edge2: Vector3D::between_points(vertex0, &self.positions[face.positions[2]])
+        }
+    }
+}
+
+impl SceneObject for TriangleMesh {
+    fn intersect(&self, ray: &Ray3D) -> Option<Hit<'_>> {
+        let nearest = self.bvh.intersect(ray, |ray, index| {
+            let geometry = self.get_face_geometry(&self.faces[index]);
+            intersect_triangle(ray, geometry.vertex0, &geometry.edge1, &geometry.edge2)
+        });
+        let (index, distance) = nearest?;
+
+        let face = &self.faces[index];
+        let geometry = self.get_face_geometry(face);
+        let normal = Vector3D::cross(&geometry.edge1, &geometry.edge2).to_unit();
+        let material = match face.material {
+            Some(material) => &self.materials[material],
+            None => &self.default_material
+        };
+
+        let mut hit = Hit::new(ray, distance, &normal, &**material);
+        let (w0, w1, w2) = barycentric(geometry.vertex0, &geometry.edge1, &geometry.edge2, &hit.point);
+
+        // Without texture coordinates, `u` and `v` are the weights of the
+        // second and third vertex.
+        let uvs = match face.uvs {
+            Some(uvs) => [self.uvs[uvs[0]], self.uvs[uvs[1]], self.uvs[uvs[2]]],
+            None => [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)]
+        };
+        hit.uv = (
+            w0 * uvs[0].0 + w1 * uvs[1].0 + w2 * uvs[2].0,
+            w0 * uvs[0].1 + w1 * uvs[1].1 + w2 * uvs[2].1
+        );
+        let (tangent, bitangent) = uv_derivatives(&geometry.edge1, &geometry.edge2, &uvs);
+        hit.set_tangents(&tangent, &bitangent);
+
+        if let Some(normals) = face.normals {
+            let normal = &self.normals[normals[0]] * w0 + &self.normals[normals[1]] * w1 + &self.normals[normals[2]] * w2;
+            hit.set_shading_normal(&normal.to_unit());
+        }
+        if !self.colors.is_empty() {
+            let positions = face.positions;
+            hit.vertex_color = Some(&self.colors[positions[0]] * w0 + &self.colors[positions[1]] * w1 + &self.colors[positions[2]] * w2);
+        }
+        Some(hit)
+    }
+
+    fn get_bounds(&self) -> Option<Aabb> {
+        Some(self.bounds.clone())
+    }
+}
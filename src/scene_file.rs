@@ -0,0 +1,1127 @@
+use std::collections::{HashMap};
+use std::error::{Error};
+use std::fmt;
+use std::fs::{File};
+use std::io;
+use std::io::{Read};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc};
+use std::str::{FromStr};
+
+use color::{ColorRGB};
+use geometry::{Point3D, Vector3D, Direction3D, Transform};
+use image::{load_image};
+use material::{Material, MaterialBuilder, Fresnel, Model};
+use mesh::{load_mesh};
+use scene::{Scene, SceneObject, SceneLight, Sphere, Plane, Triangle, TriangleMesh, Instance, Camera};
+use scene::{AxisAlignedBox, Cylinder, Cone, Disk, Torus, Csg, CsgOperation};
+use table::{Table};
+use texture::{Texture, ImageTexture, Filter, WrapMode};
+use texture::{Space, Mapping, Checker, Stripes, Gradient, Noise, Turbulence, Marble, Wood};
+
+#[derive(Debug)]
+pub struct SceneFile {
+    file_name: String
+}
+
+impl SceneFile {
+    pub fn new(file_name: &str) -> SceneFile {
+        SceneFile {
+            file_name: file_name.to_string()
+        }
+    }
+
+    pub fn load(&self) -> Result<(Scene, Camera), SceneFileError> {
+        let file_path = Path::new(&self.file_name);
+
+        let mut file = try!(File::open(file_path));
+        let mut text = String::new();
+        try!(file.read_to_string(&mut text));
+
+        let directory = match file_path.parent() {
+            Some(parent) => parent.to_path_buf(),
+            None => PathBuf::new()
+        };
+        let tokens = tokenize(&text);
+        let mut parser = Parser::new(tokens, directory);
+        parser.parse_file()
+    }
+}
+
+// Mesh files named in `text` are looked up relative to the current directory.
+pub fn parse_scene(text: &str) -> Result<(Scene, Camera), SceneFileError> {
+    let tokens = tokenize(text);
+    let mut parser = Parser::new(tokens, PathBuf::new());
+    parser.parse_file()
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Debug)]
+pub enum SceneFileError {
+    Io(io::Error),
+    Parse {
+        line: usize,
+        column: usize,
+        message: String
+    }
+}
+
+impl SceneFileError {
+    fn parse(line: usize, column: usize, message: String) -> SceneFileError {
+        SceneFileError::Parse {
+            line: line,
+            column: column,
+            message: message
+        }
+    }
+}
+
+impl fmt::Display for SceneFileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SceneFileError::Io(ref error) => write!(f, "{}", error),
+            SceneFileError::Parse {line, column, ref message} => write!(f, "{}:{}: {}", line, column, message)
+        }
+    }
+}
+
+impl Error for SceneFileError {}
+
+impl From<io::Error> for SceneFileError {
+    fn from(error: io::Error) -> SceneFileError {
+        SceneFileError::Io(error)
+    }
+}
+
+//------------------------------------------------------------------------------
+
+#[derive(Clone, Debug)]
+struct Token {
+    text: String,
+    line: usize,
+    column: usize
+}
+
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+
+    for (line_index, line) in text.lines().enumerate() {
+        let mut current: Option<Token> = None;
+
+        for (column_index, c) in line.chars().enumerate() {
+            if c == '#' {
+                break;
+            }
+
+            let is_separator = c.is_whitespace() || c == '{' || c == '}';
+            if is_separator {
+                if let Some(token) = current.take() {
+                    tokens.push(token);
+                }
+                if !c.is_whitespace() {
+                    tokens.push(Token {
+                        text: c.to_string(),
+                        line: line_index + 1,
+                        column: column_index + 1
+                    });
+                }
+            } else {
+                match current {
+                    Some(ref mut token) => token.text.push(c),
+                    None => {
+                        current = Some(Token {
+                            text: c.to_string(),
+                            line: line_index + 1,
+                            column: column_index + 1
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(token) = current.take() {
+            tokens.push(token);
+        }
+    }
+
+    tokens
+}
+
+//------------------------------------------------------------------------------
+
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    directory: PathBuf,
+    materials: HashMap<String, Arc<Material>>,
+    textures: HashMap<String, Arc<Texture>>,
+    meshes: HashMap<String, Arc<TriangleMesh>>,
+    images: HashMap<String, Arc<Table<ColorRGB>>>
+}
+
+enum SceneItem {
+    Object(Box<SceneObject+Sync+Send>),
+    Light(Box<SceneLight>)
+}
+
+struct TextureSettings {
+    image: Option<Token>,
+    pattern: Option<Token>,
+    filter: Filter,
+    wrap_mode: WrapMode,
+    colors: (ColorRGB, ColorRGB),
+    space: Option<Space>,
+    scale: f32,
+    direction: Direction3D,
+    octaves: u32,
+    turbulence: f32,
+    seed: u32
+}
+
+struct CameraSettings {
+    image_dimensions: Option<(usize, usize)>,
+    field_of_view: Option<f32>,
+    plane_dimensions: Option<(f32, f32)>,
+    distance_to_plane: f32,
+    position: Point3D,
+    look_at: Point3D
+}
+
+impl Parser {
+    fn new(tokens: Vec<Token>, directory: PathBuf) -> Parser {
+        Parser {
+            tokens: tokens,
+            position: 0,
+            directory: directory,
+            materials: HashMap::new(),
+            textures: HashMap::new(),
+            meshes: HashMap::new(),
+            images: HashMap::new()
+        }
+    }
+
+    fn parse_file(&mut self) -> Result<(Scene, Camera), SceneFileError> {
+        let mut background_color = ColorRGB::black().clone();
+        let mut refractive_index = 1.0;
+        let mut max_ray_depth = 5;
+        let mut camera = None;
+        let mut items = Vec::new();
+
+        while let Some(token) = self.next_token() {
+            match &*token.text {
+                "background" => background_color = try!(self.parse_color()),
+                "refractive_index" => refractive_index = try!(self.parse_number()),
+                "max_ray_depth" => max_ray_depth = try!(self.parse_number()),
+                "camera" => {
+                    if camera.is_some() {
+                        return Err(error_at(&token, "duplicate `camera` block".to_string()));
+                    }
+                    camera = Some(try!(self.parse_camera(&token)));
+                },
+                "material" => {
+                    let name = try!(self.expect_token());
+                    let material = try!(self.parse_material_block());
+                    self.materials.insert(name.text, material);
+                },
+                "texture" => {
+                    let name = try!(self.expect_token());
+                    let texture = try!(self.parse_texture_block(&name));
+                    self.textures.insert(name.text, texture);
+                },
+                "sphere" => {
+                    let (sphere, transform) = try!(self.parse_sphere(&token));
+                    items.push(object_item(sphere, transform));
+                },
+                "plane" => {
+                    let (plane, transform) = try!(self.parse_plane(&token));
+                    items.push(object_item(plane, transform));
+                },
+                "triangle" => {
+                    let (triangle, transform) = try!(self.parse_triangle(&token));
+                    items.push(object_item(triangle, transform));
+                },
+                "box" => {
+                    let (axis_aligned_box, transform) = try!(self.parse_box(&token));
+                    items.push(object_item(axis_aligned_box, transform));
+                },
+                "cylinder" => {
+                    let (cylinder, transform) = try!(self.parse_cylinder(&token));
+                    items.push(object_item(cylinder, transform));
+                },
+                "cone" => {
+                    let (cone, transform) = try!(self.parse_cone(&token));
+                    items.push(object_item(cone, transform));
+                },
+                "disk" => {
+                    let (disk, transform) = try!(self.parse_disk(&token));
+                    items.push(object_item(disk, transform));
+                },
+                "torus" => {
+                    let (torus, transform) = try!(self.parse_torus(&token));
+                    items.push(object_item(torus, transform));
+                },
+                "mesh" => items.push(try!(self.parse_mesh(&token))),
+                "union" | "intersection" | "difference" => {
+                    let (csg, transform) = try!(self.parse_csg(&token));
+                    items.push(object_item(csg, transform));
+                },
+                "light" => {
+                    let light = try!(self.parse_light(&token));
+                    items.push(SceneItem::Light(Box::new(light)));
+                },
+                _ => return Err(error_at(&token, format!("unknown statement `{}`", token.text)))
+            }
+        }
+
+        let camera = match camera {
+            Some(camera) => camera,
+            None => return Err(SceneFileError::parse(1, 1, "scene does not contain a `camera` block".to_string()))
+        };
+
+        let mut scene = Scene::new(&background_color, refractive_index, max_ray_depth);
+        for item in items {
+            match item {
+                SceneItem::Object(object) => scene.add_object(object),
+                SceneItem::Light(light) => scene.add_light_source(light)
+            }
+        }
+        scene.finalize();
+
+        Ok((scene, camera))
+    }
+
+    fn parse_camera(&mut self, start: &Token) -> Result<Camera, SceneFileError> {
+        let mut settings = CameraSettings {
+            image_dimensions: None,
+            field_of_view: None,
+            plane_dimensions: None,
+            distance_to_plane: 1.0,
+            position: Point3D::origin().clone(),
+            look_at: Point3D::from_xyz(0.0, 0.0, 1.0)
+        };
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "image_dimensions" => {
+                    let width = try!(self.parse_dimension(&token));
+                    let height = try!(self.parse_dimension(&token));
+                    settings.image_dimensions = Some((width, height));
+                },
+                "field_of_view" => settings.field_of_view = Some(try!(self.parse_number())),
+                "plane_dimensions" => {
+                    let width = try!(self.parse_number());
+                    let height = try!(self.parse_number());
+                    settings.plane_dimensions = Some((width, height));
+                },
+                "distance_to_plane" => settings.distance_to_plane = try!(self.parse_number()),
+                "position" => settings.position = try!(self.parse_point()),
+                "look_at" => settings.look_at = try!(self.parse_point()),
+                _ => return Err(unknown_property(&token, "camera"))
+            }
+        }
+
+        let image_dimensions = match settings.image_dimensions {
+            Some(image_dimensions) => image_dimensions,
+            None => return Err(error_at(start, "`camera` requires `image_dimensions`".to_string()))
+        };
+
+        match (settings.field_of_view, settings.plane_dimensions) {
+            (Some(field_of_view), None) => Ok(Camera::from_fov(image_dimensions, field_of_view, settings.distance_to_plane, &settings.position, &settings.look_at)),
+            (None, Some(plane_dimensions)) => Ok(Camera::from_dimensions(image_dimensions, plane_dimensions, settings.distance_to_plane, &settings.position, &settings.look_at)),
+            _ => Err(error_at(start, "`camera` requires exactly one of `field_of_view` or `plane_dimensions`".to_string()))
+        }
+    }
+
+    fn parse_material_block(&mut self) -> Result<Arc<Material>, SceneFileError> {
+        let mut builder = MaterialBuilder::new();
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "color" => { builder.color(&try!(self.parse_color())); },
+                "diffuse" => { builder.diffuse(try!(self.parse_number())); },
+                "specular" => { builder.specular(try!(self.parse_number())); },
+                "shininess" => { builder.shininess(try!(self.parse_number())); },
+                "reflection" => { builder.reflection(try!(self.parse_number())); },
+                "refraction" => { builder.refraction(try!(self.parse_number())); },
+                "refractive_index" => { builder.refractive_index(try!(self.parse_number())); },
+                "fresnel" => {
+                    let value = try!(self.expect_token());
+                    builder.fresnel(match &*value.text {
+                        "none" => Fresnel::None,
+                        "exact" => Fresnel::Exact,
+                        "schlick" => Fresnel::Schlick,
+                        _ => return Err(error_at(&value, format!("unknown Fresnel mode `{}`", value.text)))
+                    });
+                },
+                "model" => {
+                    let value = try!(self.expect_token());
+                    builder.model(match &*value.text {
+                        "phong" => Model::Phong,
+                        "metallic_roughness" => Model::MetallicRoughness,
+                        _ => return Err(error_at(&value, format!("unknown material model `{}`", value.text)))
+                    });
+                },
+                "metallic" => { builder.metallic(try!(self.parse_number())); },
+                "roughness" => { builder.roughness(try!(self.parse_number())); },
+                "color_map" => { builder.color_map(&try!(self.parse_texture_reference())); },
+                "diffuse_map" => { builder.diffuse_map(&try!(self.parse_texture_reference())); },
+                "specular_map" => { builder.specular_map(&try!(self.parse_texture_reference())); },
+                "reflection_map" => { builder.reflection_map(&try!(self.parse_texture_reference())); },
+                "metallic_map" => { builder.metallic_map(&try!(self.parse_texture_reference())); },
+                "roughness_map" => { builder.roughness_map(&try!(self.parse_texture_reference())); },
+                "normal_map" => { builder.normal_map(&try!(self.parse_texture_reference())); },
+                "bump_map" => { builder.bump_map(&try!(self.parse_texture_reference())); },
+                "bump_scale" => { builder.bump_scale(try!(self.parse_number())); },
+                _ => return Err(unknown_property(&token, "material"))
+            }
+        }
+
+        Ok(builder.to_material())
+    }
+
+    fn parse_material_reference(&mut self) -> Result<Arc<Material>, SceneFileError> {
+        let is_block = match self.peek_token() {
+            Some(token) => token.text == "{",
+            None => false
+        };
+        if is_block {
+            return self.parse_material_block();
+        }
+
+        let name = try!(self.expect_token());
+        match self.materials.get(&name.text) {
+            Some(material) => Ok(material.clone()),
+            None => Err(error_at(&name, format!("unknown material `{}`", name.text)))
+        }
+    }
+
+    fn parse_sphere(&mut self, start: &Token) -> Result<(Sphere, Option<Transform>), SceneFileError> {
+        let mut center = None;
+        let mut radius = None;
+        let mut material = None;
+        let mut transform = None;
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "center" => center = Some(try!(self.parse_point())),
+                "radius" => radius = Some(try!(self.parse_number())),
+                "material" => material = Some(try!(self.parse_material_reference())),
+                "transform" => transform = Some(try!(self.parse_transform())),
+                _ => return Err(unknown_property(&token, "sphere"))
+            }
+        }
+
+        let center = try!(required(center, start, "sphere", "center"));
+        let radius = try!(required(radius, start, "sphere", "radius"));
+        let material = material.unwrap_or_else(|| MaterialBuilder::new().to_material());
+
+        Ok((Sphere::new(&center, radius, &material), transform))
+    }
+
+    fn parse_plane(&mut self, start: &Token) -> Result<(Plane, Option<Transform>), SceneFileError> {
+        let mut normal = None;
+        let mut d = None;
+        let mut origin = None;
+        let mut material = None;
+        let mut transform = None;
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "normal" => normal = Some(try!(self.parse_direction(&token))),
+                "d" => d = Some(try!(self.parse_number())),
+                "origin" => origin = Some(try!(self.parse_point())),
+                "material" => material = Some(try!(self.parse_material_reference())),
+                "transform" => transform = Some(try!(self.parse_transform())),
+                _ => return Err(unknown_property(&token, "plane"))
+            }
+        }
+
+        let normal: Vector3D = try!(required(normal, start, "plane", "normal"));
+        let material = material.unwrap_or_else(|| MaterialBuilder::new().to_material());
+
+        match (d, origin) {
+            (Some(d), None) => Ok((Plane::from_d_vector(d, &normal, &material), transform)),
+            (None, Some(origin)) => Ok((Plane::from_origin_normal(&origin, &normal.to_unit(), &material), transform)),
+            _ => Err(error_at(start, "`plane` requires exactly one of `d` or `origin`".to_string()))
+        }
+    }
+
+    fn parse_triangle(&mut self, start: &Token) -> Result<(Triangle, Option<Transform>), SceneFileError> {
+        let mut vertices = None;
+        let mut normals = None;
+        let mut uvs = None;
+        let mut material = None;
+        let mut transform = None;
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "vertices" => {
+                    let vertex0 = try!(self.parse_point());
+                    let vertex1 = try!(self.parse_point());
+                    let vertex2 = try!(self.parse_point());
+                    vertices = Some([vertex0, vertex1, vertex2]);
+                },
+                "normals" => {
+                    let normal0 = try!(self.parse_direction(&token)).to_unit();
+                    let normal1 = try!(self.parse_direction(&token)).to_unit();
+                    let normal2 = try!(self.parse_direction(&token)).to_unit();
+                    normals = Some([normal0, normal1, normal2]);
+                },
+                "uvs" => {
+                    let mut values = [(0.0, 0.0); 3];
+                    for value in values.iter_mut() {
+                        let u = try!(self.parse_number());
+                        let v = try!(self.parse_number());
+                        *value = (u, v);
+                    }
+                    uvs = Some(values);
+                },
+                "material" => material = Some(try!(self.parse_material_reference())),
+                "transform" => transform = Some(try!(self.parse_transform())),
+                _ => return Err(unknown_property(&token, "triangle"))
+            }
+        }
+
+        let vertices = try!(required(vertices, start, "triangle", "vertices"));
+        let material = material.unwrap_or_else(|| MaterialBuilder::new().to_material());
+
+        let mut triangle = Triangle::new(&vertices[0], &vertices[1], &vertices[2], &material);
+        if let Some(normals) = normals {
+            triangle.set_vertex_normals(&normals);
+        }
+        if let Some(uvs) = uvs {
+            triangle.set_vertex_uvs(&uvs);
+        }
+        Ok((triangle, transform))
+    }
+
+    fn parse_box(&mut self, start: &Token) -> Result<(AxisAlignedBox, Option<Transform>), SceneFileError> {
+        let mut min = None;
+        let mut max = None;
+        let mut material = None;
+        let mut transform = None;
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "min" => min = Some(try!(self.parse_point())),
+                "max" => max = Some(try!(self.parse_point())),
+                "material" => material = Some(try!(self.parse_material_reference())),
+                "transform" => transform = Some(try!(self.parse_transform())),
+                _ => return Err(unknown_property(&token, "box"))
+            }
+        }
+
+        let min = try!(required(min, start, "box", "min"));
+        let max = try!(required(max, start, "box", "max"));
+        let material = material.unwrap_or_else(|| MaterialBuilder::new().to_material());
+
+        Ok((AxisAlignedBox::new(&min, &max, &material), transform))
+    }
+
+    fn parse_cylinder(&mut self, start: &Token) -> Result<(Cylinder, Option<Transform>), SceneFileError> {
+        let mut base = None;
+        let mut top = None;
+        let mut radius = None;
+        let mut material = None;
+        let mut transform = None;
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "base" => base = Some(try!(self.parse_point())),
+                "top" => top = Some(try!(self.parse_point())),
+                "radius" => radius = Some(try!(self.parse_number())),
+                "material" => material = Some(try!(self.parse_material_reference())),
+                "transform" => transform = Some(try!(self.parse_transform())),
+                _ => return Err(unknown_property(&token, "cylinder"))
+            }
+        }
+
+        let base = try!(required(base, start, "cylinder", "base"));
+        let top = try!(required(top, start, "cylinder", "top"));
+        let radius = try!(required(radius, start, "cylinder", "radius"));
+        try!(check_axis(&base, &top, start, "cylinder"));
+        let material = material.unwrap_or_else(|| MaterialBuilder::new().to_material());
+
+        Ok((Cylinder::new(&base, &top, radius, &material), transform))
+    }
+
+    fn parse_cone(&mut self, start: &Token) -> Result<(Cone, Option<Transform>), SceneFileError> {
+        let mut base = None;
+        let mut top = None;
+        let mut base_radius = None;
+        let mut top_radius = 0.0;
+        let mut material = None;
+        let mut transform = None;
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "base" => base = Some(try!(self.parse_point())),
+                "top" => top = Some(try!(self.parse_point())),
+                "base_radius" => base_radius = Some(try!(self.parse_number())),
+                "top_radius" => top_radius = try!(self.parse_number()),
+                "material" => material = Some(try!(self.parse_material_reference())),
+                "transform" => transform = Some(try!(self.parse_transform())),
+                _ => return Err(unknown_property(&token, "cone"))
+            }
+        }
+
+        let base = try!(required(base, start, "cone", "base"));
+        let top = try!(required(top, start, "cone", "top"));
+        let base_radius = try!(required(base_radius, start, "cone", "base_radius"));
+        try!(check_axis(&base, &top, start, "cone"));
+        let material = material.unwrap_or_else(|| MaterialBuilder::new().to_material());
+
+        Ok((Cone::new(&base, &top, base_radius, top_radius, &material), transform))
+    }
+
+    fn parse_disk(&mut self, start: &Token) -> Result<(Disk, Option<Transform>), SceneFileError> {
+        let mut center = None;
+        let mut normal = None;
+        let mut radius = None;
+        let mut material = None;
+        let mut transform = None;
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "center" => center = Some(try!(self.parse_point())),
+                "normal" => normal = Some(try!(self.parse_direction(&token))),
+                "radius" => radius = Some(try!(self.parse_number())),
+                "material" => material = Some(try!(self.parse_material_reference())),
+                "transform" => transform = Some(try!(self.parse_transform())),
+                _ => return Err(unknown_property(&token, "disk"))
+            }
+        }
+
+        let center = try!(required(center, start, "disk", "center"));
+        let normal: Vector3D = try!(required(normal, start, "disk", "normal"));
+        let radius = try!(required(radius, start, "disk", "radius"));
+        let material = material.unwrap_or_else(|| MaterialBuilder::new().to_material());
+
+        Ok((Disk::new(&center, &normal.to_unit(), radius, &material), transform))
+    }
+
+    fn parse_torus(&mut self, start: &Token) -> Result<(Torus, Option<Transform>), SceneFileError> {
+        let mut center = None;
+        let mut axis = None;
+        let mut major_radius = None;
+        let mut minor_radius = None;
+        let mut material = None;
+        let mut transform = None;
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "center" => center = Some(try!(self.parse_point())),
+                "axis" => axis = Some(try!(self.parse_direction(&token))),
+                "major_radius" => major_radius = Some(try!(self.parse_number())),
+                "minor_radius" => minor_radius = Some(try!(self.parse_number())),
+                "material" => material = Some(try!(self.parse_material_reference())),
+                "transform" => transform = Some(try!(self.parse_transform())),
+                _ => return Err(unknown_property(&token, "torus"))
+            }
+        }
+
+        let center = try!(required(center, start, "torus", "center"));
+        let axis: Vector3D = try!(required(axis, start, "torus", "axis"));
+        let major_radius = try!(required(major_radius, start, "torus", "major_radius"));
+        let minor_radius = try!(required(minor_radius, start, "torus", "minor_radius"));
+        let material = material.unwrap_or_else(|| MaterialBuilder::new().to_material());
+
+        Ok((Torus::new(&center, &axis.to_unit(), major_radius, minor_radius, &material), transform))
+    }
+
+    // A texture is either an image file or a generated pattern.
+    fn parse_texture_block(&mut self, start: &Token) -> Result<Arc<Texture>, SceneFileError> {
+        let mut settings = TextureSettings {
+            image: None,
+            pattern: None,
+            filter: Filter::Bilinear,
+            wrap_mode: WrapMode::Repeat,
+            colors: (ColorRGB::black().clone(), ColorRGB::white().clone()),
+            space: None,
+            scale: 1.0,
+            direction: Direction3D::unit_x().clone(),
+            octaves: 6,
+            turbulence: 1.0,
+            seed: 0
+        };
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "image" => settings.image = Some(try!(self.expect_token())),
+                "pattern" => settings.pattern = Some(try!(self.expect_token())),
+                "filter" => {
+                    let value = try!(self.expect_token());
+                    settings.filter = match &*value.text {
+                        "nearest" => Filter::Nearest,
+                        "bilinear" => Filter::Bilinear,
+                        _ => return Err(error_at(&value, format!("unknown filter `{}`", value.text)))
+                    };
+                },
+                "wrap" => {
+                    let value = try!(self.expect_token());
+                    settings.wrap_mode = match &*value.text {
+                        "repeat" => WrapMode::Repeat,
+                        "clamp" => WrapMode::Clamp,
+                        "mirror" => WrapMode::Mirror,
+                        _ => return Err(error_at(&value, format!("unknown wrap mode `{}`", value.text)))
+                    };
+                },
+                "colors" => {
+                    let color1 = try!(self.parse_color());
+                    let color2 = try!(self.parse_color());
+                    settings.colors = (color1, color2);
+                },
+                "space" => {
+                    let value = try!(self.expect_token());
+                    settings.space = match &*value.text {
+                        "surface" => Some(Space::Surface),
+                        "solid" => Some(Space::Solid),
+                        _ => return Err(error_at(&value, format!("unknown texture space `{}`", value.text)))
+                    };
+                },
+                "scale" => {
+                    settings.scale = try!(self.parse_number());
+                    if settings.scale == 0.0 {
+                        return Err(error_at(&token, "texture scale must not be zero".to_string()));
+                    }
+                },
+                "direction" => settings.direction = Direction3D::from_vector(&try!(self.parse_direction(&token))),
+                "octaves" => settings.octaves = try!(self.parse_number()),
+                "turbulence" => settings.turbulence = try!(self.parse_number()),
+                "seed" => settings.seed = try!(self.parse_number()),
+                _ => return Err(unknown_property(&token, "texture"))
+            }
+        }
+
+        match (settings.image.take(), settings.pattern.take()) {
+            (Some(file), None) => {
+                let image = try!(self.load_texture_image(&file));
+                Ok(Arc::new(ImageTexture::new(image, settings.filter, settings.wrap_mode)))
+            },
+            (None, Some(pattern)) => create_pattern(&pattern, &settings),
+            _ => Err(error_at(start, "`texture` requires exactly one of `image` or `pattern`".to_string()))
+        }
+    }
+
+    fn parse_texture_reference(&mut self) -> Result<Arc<Texture>, SceneFileError> {
+        let start = match self.peek_token() {
+            Some(token) if token.text == "{" => Some(token.clone()),
+            _ => None
+        };
+        if let Some(start) = start {
+            return self.parse_texture_block(&start);
+        }
+
+        let name = try!(self.expect_token());
+        match self.textures.get(&name.text) {
+            Some(texture) => Ok(texture.clone()),
+            None => Err(error_at(&name, format!("unknown texture `{}`", name.text)))
+        }
+    }
+
+    // Images are loaded once and shared by every texture that uses them.
+    fn load_texture_image(&mut self, file: &Token) -> Result<Arc<Table<ColorRGB>>, SceneFileError> {
+        let path = self.directory.join(&file.text).to_string_lossy().into_owned();
+        if let Some(image) = self.images.get(&path) {
+            return Ok(image.clone());
+        }
+
+        let image = match load_image(&path) {
+            Ok(image) => Arc::new(image),
+            Err(error) => return Err(error_at(file, format!("cannot load image `{}`: {}", file.text, error)))
+        };
+        self.images.insert(path, image.clone());
+        Ok(image)
+    }
+
+    // Meshes without a `material` are loaded once per file and shared by every
+    // block that uses them.
+    fn parse_mesh(&mut self, start: &Token) -> Result<SceneItem, SceneFileError> {
+        let mut file = None;
+        let mut material = None;
+        let mut transform = None;
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "file" => file = Some(try!(self.expect_token())),
+                "material" => material = Some(try!(self.parse_material_reference())),
+                "transform" => transform = Some(try!(self.parse_transform())),
+                _ => return Err(unknown_property(&token, "mesh"))
+            }
+        }
+
+        let file = try!(required(file, start, "mesh", "file"));
+        let path = self.directory.join(&file.text).to_string_lossy().into_owned();
+        if material.is_none() {
+            if let Some(mesh) = self.meshes.get(&path) {
+                let transform = transform.unwrap_or_else(Transform::identity);
+                return Ok(SceneItem::Object(Box::new(Instance::new(mesh.clone(), &transform))));
+            }
+        }
+
+        let mut mesh = match load_mesh(&path) {
+            Ok(mesh) => mesh,
+            Err(error) => return Err(error_at(&file, format!("cannot load mesh `{}`: {}", file.text, error)))
+        };
+
+        // An explicit material replaces the ones from the mesh file.
+        match material {
+            Some(material) => {
+                mesh.set_material(&material);
+                Ok(object_item(TriangleMesh::new(&mesh), transform))
+            },
+            None => {
+                let shared = Arc::new(TriangleMesh::new(&mesh));
+                self.meshes.insert(path, shared.clone());
+                let transform = transform.unwrap_or_else(Transform::identity);
+                Ok(SceneItem::Object(Box::new(Instance::new(shared, &transform))))
+            }
+        }
+    }
+
+    // Combines the objects in the block from first to last, so a difference
+    // removes every later object from the first one.
+    fn parse_csg(&mut self, start: &Token) -> Result<(Csg, Option<Transform>), SceneFileError> {
+        let operation = match &*start.text {
+            "union" => CsgOperation::Union,
+            "intersection" => CsgOperation::Intersection,
+            _ => CsgOperation::Difference
+        };
+        let mut objects = Vec::new();
+        let mut transform = None;
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "transform" => transform = Some(try!(self.parse_transform())),
+                _ => objects.push(try!(self.parse_solid(&token, &start.text)))
+            }
+        }
+
+        if objects.len() < 2 {
+            return Err(error_at(start, format!("`{}` requires at least two objects", start.text)));
+        }
+
+        let mut objects = objects.into_iter();
+        let first = objects.next().unwrap();
+        let second = objects.next().unwrap();
+        let csg = objects.fold(Csg::new(operation, first, second), |csg, object| {
+            Csg::new(operation, Box::new(csg), object)
+        });
+
+        Ok((csg, transform))
+    }
+
+    // Only closed objects can be combined, since the others have no inside.
+    fn parse_solid(&mut self, token: &Token, block: &str) -> Result<Box<SceneObject+Sync+Send>, SceneFileError> {
+        match &*token.text {
+            "sphere" => {
+                let (sphere, transform) = try!(self.parse_sphere(token));
+                Ok(boxed_object(sphere, transform))
+            },
+            "box" => {
+                let (axis_aligned_box, transform) = try!(self.parse_box(token));
+                Ok(boxed_object(axis_aligned_box, transform))
+            },
+            "cylinder" => {
+                let (cylinder, transform) = try!(self.parse_cylinder(token));
+                Ok(boxed_object(cylinder, transform))
+            },
+            "cone" => {
+                let (cone, transform) = try!(self.parse_cone(token));
+                Ok(boxed_object(cone, transform))
+            },
+            "torus" => {
+                let (torus, transform) = try!(self.parse_torus(token));
+                Ok(boxed_object(torus, transform))
+            },
+            "union" | "intersection" | "difference" => {
+                let (csg, transform) = try!(self.parse_csg(token));
+                Ok(boxed_object(csg, transform))
+            },
+            "plane" | "triangle" | "disk" | "mesh" => {
+                Err(error_at(token, format!("`{}` cannot be used in `{}` because it is not closed", token.text, block)))
+            },
+            _ => Err(unknown_property(token, block))
+        }
+    }
+
+    // Steps are applied in the order they are written.
+    fn parse_transform(&mut self) -> Result<Transform, SceneFileError> {
+        let mut transform = Transform::identity();
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            let step = match &*token.text {
+                "translate" => Transform::translation(&try!(self.parse_vector())),
+                "rotate" => {
+                    let axis = try!(self.parse_vector());
+                    let angle = try!(self.parse_number());
+                    if axis.magnitude() == 0.0 {
+                        return Err(error_at(&token, "rotation axis must not be zero".to_string()));
+                    }
+                    Transform::rotation(&axis.to_unit(), angle)
+                },
+                "scale" => {
+                    let x = try!(self.parse_number());
+                    let y = try!(self.parse_number());
+                    let z = try!(self.parse_number());
+                    if x == 0.0 || y == 0.0 || z == 0.0 {
+                        return Err(error_at(&token, "scale factors must not be zero".to_string()));
+                    }
+                    Transform::scale(x, y, z)
+                },
+                _ => return Err(unknown_property(&token, "transform"))
+            };
+            transform = transform.then(&step);
+        }
+
+        Ok(transform)
+    }
+
+    fn parse_light(&mut self, start: &Token) -> Result<SceneLight, SceneFileError> {
+        let mut center = None;
+        let mut radius = None;
+        let mut color = None;
+
+        try!(self.expect_symbol("{"));
+        while let Some(token) = try!(self.next_property()) {
+            match &*token.text {
+                "center" => center = Some(try!(self.parse_point())),
+                "radius" => radius = Some(try!(self.parse_number())),
+                "color" => color = Some(try!(self.parse_color())),
+                _ => return Err(unknown_property(&token, "light"))
+            }
+        }
+
+        let center = try!(required(center, start, "light", "center"));
+        let radius = try!(required(radius, start, "light", "radius"));
+        let color = try!(required(color, start, "light", "color"));
+
+        Ok(SceneLight::new(&center, radius, &color))
+    }
+
+    fn parse_point(&mut self) -> Result<Point3D, SceneFileError> {
+        let x = try!(self.parse_number());
+        let y = try!(self.parse_number());
+        let z = try!(self.parse_number());
+        Ok(Point3D::from_xyz(x, y, z))
+    }
+
+    fn parse_vector(&mut self) -> Result<Vector3D, SceneFileError> {
+        let x = try!(self.parse_number());
+        let y = try!(self.parse_number());
+        let z = try!(self.parse_number());
+        Ok(Vector3D::from_xyz(x, y, z))
+    }
+
+    // A vector that gives a direction, which must not be zero.
+    fn parse_direction(&mut self, token: &Token) -> Result<Vector3D, SceneFileError> {
+        let vector = try!(self.parse_vector());
+        if vector.magnitude() == 0.0 {
+            return Err(error_at(token, format!("`{}` must not be zero", token.text)));
+        }
+        Ok(vector)
+    }
+
+    fn parse_color(&mut self) -> Result<ColorRGB, SceneFileError> {
+        let red = try!(self.parse_number());
+        let green = try!(self.parse_number());
+        let blue = try!(self.parse_number());
+        Ok(ColorRGB::from_rgb(red, green, blue))
+    }
+
+    fn parse_number<T: FromStr>(&mut self) -> Result<T, SceneFileError> {
+        let token = try!(self.expect_token());
+        match token.text.parse::<T>() {
+            Ok(value) => Ok(value),
+            Err(_) => Err(error_at(&token, format!("invalid number `{}`", token.text)))
+        }
+    }
+
+    // Image sizes are counted in pixels, so they must be at least one.
+    fn parse_dimension(&mut self, property: &Token) -> Result<usize, SceneFileError> {
+        let token = try!(self.expect_token());
+        match token.text.parse::<usize>() {
+            Ok(0) => Err(error_at(&token, format!("`{}` must be positive", property.text))),
+            Ok(value) => Ok(value),
+            Err(_) => Err(error_at(&token, format!("invalid number `{}`", token.text)))
+        }
+    }
+
+    // Returns the next property name inside a block, or `None` once the
+    // closing brace has been consumed.
+    fn next_property(&mut self) -> Result<Option<Token>, SceneFileError> {
+        let token = try!(self.expect_token());
+        match &*token.text {
+            "}" => Ok(None),
+            "{" => Err(error_at(&token, "expected a property name, found `{`".to_string())),
+            _ => Ok(Some(token))
+        }
+    }
+
+    fn expect_symbol(&mut self, symbol: &str) -> Result<(), SceneFileError> {
+        let token = try!(self.expect_token());
+        if token.text == symbol {
+            Ok(())
+        } else {
+            Err(error_at(&token, format!("expected `{}`, found `{}`", symbol, token.text)))
+        }
+    }
+
+    fn expect_token(&mut self) -> Result<Token, SceneFileError> {
+        match self.next_token() {
+            Some(token) => Ok(token),
+            None => {
+                let (line, column) = match self.tokens.last() {
+                    Some(token) => (token.line, token.column + token.text.chars().count()),
+                    None => (1, 1)
+                };
+                Err(SceneFileError::parse(line, column, "unexpected end of file".to_string()))
+            }
+        }
+    }
+
+    fn peek_token(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next_token(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        if token.is_some() {
+            self.position += 1;
+        }
+        token
+    }
+}
+
+fn object_item<T: SceneObject + Sync + Send + 'static>(object: T, transform: Option<Transform>) -> SceneItem {
+    SceneItem::Object(boxed_object(object, transform))
+}
+
+fn boxed_object<T: SceneObject + Sync + Send + 'static>(object: T, transform: Option<Transform>) -> Box<SceneObject+Sync+Send> {
+    match transform {
+        Some(transform) => Box::new(Instance::new(Arc::new(object), &transform)),
+        None => Box::new(object)
+    }
+}
+
+// Patterns on the surface default to texture coordinates, while the ones that
+// imitate solid materials default to space.
+fn create_pattern(pattern: &Token, settings: &TextureSettings) -> Result<Arc<Texture>, SceneFileError> {
+    let default_space = match &*pattern.text {
+        "checker" | "stripes" | "gradient" => Space::Surface,
+        _ => Space::Solid
+    };
+    let mapping = Mapping::new(settings.space.unwrap_or(default_space), settings.scale);
+    let colors = &settings.colors;
+
+    let texture: Arc<Texture> = match &*pattern.text {
+        "checker" => Arc::new(Checker::new(&mapping, colors)),
+        "stripes" => Arc::new(Stripes::new(&mapping, &settings.direction, colors)),
+        "gradient" => Arc::new(Gradient::new(&mapping, &settings.direction, colors)),
+        "noise" => Arc::new(Noise::new(&mapping, settings.seed, colors)),
+        "turbulence" => Arc::new(Turbulence::new(&mapping, settings.seed, settings.octaves, colors)),
+        "marble" => Arc::new(Marble::new(&mapping, settings.seed, settings.octaves, &settings.direction, settings.turbulence, colors)),
+        "wood" => Arc::new(Wood::new(&mapping, settings.seed, settings.octaves, settings.turbulence, colors)),
+        _ => return Err(error_at(pattern, format!("unknown pattern `{}`", pattern.text)))
+    };
+    Ok(texture)
+}
+
+fn error_at(token: &Token, message: String) -> SceneFileError {
+    SceneFileError::parse(token.line, token.column, message)
+}
+
+fn unknown_property(token: &Token, block: &str) -> SceneFileError {
+    error_at(token, format!("unknown `{}` property `{}`", block, token.text))
+}
+
+// Objects around an axis from `base` to `top` need the two to differ.
+fn check_axis(base: &Point3D, top: &Point3D, start: &Token, block: &str) -> Result<(), SceneFileError> {
+    if Vector3D::between_points(base, top).magnitude() == 0.0 {
+        return Err(error_at(start, format!("`{}` requires `base` and `top` to differ", block)));
+    }
+    Ok(())
+}
+
+fn required<T>(value: Option<T>, start: &Token, block: &str, property: &str) -> Result<T, SceneFileError> {
+    match value {
+        Some(value) => Ok(value),
+        None => Err(error_at(start, format!("`{}` requires `{}`", block, property)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_scene, SceneFileError};
+
+    const CAMERA: &str = "camera { image_dimensions 4 3 field_of_view 60 }\n";
+
+    fn assert_error(text: &str, line: usize, column: usize, message: &str) {
+        match parse_scene(text) {
+            Ok(_) => panic!("expected an error at {}:{}", line, column),
+            Err(SceneFileError::Parse {line: actual_line, column: actual_column, message: ref actual_message}) => {
+                assert_eq!((actual_line, actual_column), (line, column), "wrong position for `{}`", actual_message);
+                assert_eq!(actual_message, message);
+            },
+            Err(error) => panic!("expected a parse error, got `{}`", error)
+        }
+    }
+
+    #[test]
+    fn parses_a_scene_with_a_camera() {
+        let text = format!("{}# a comment\nsphere {{ center 0 0 5 radius 1 }}\nlight {{ center 0 5 0 radius 0.5 color 1 1 1 }}\n", CAMERA);
+        assert!(parse_scene(&text).is_ok());
+    }
+
+    #[test]
+    fn reports_an_unknown_statement() {
+        assert_error(&format!("{}\n  spheer {{ }}\n", CAMERA), 3, 3, "unknown statement `spheer`");
+    }
+
+    #[test]
+    fn reports_an_unknown_property() {
+        assert_error(&format!("{}sphere {{\n    center 0 0 5\n    radious 1\n}}\n", CAMERA), 4, 5, "unknown `sphere` property `radious`");
+    }
+
+    #[test]
+    fn reports_a_missing_field_at_the_start_of_the_block() {
+        assert_error(&format!("{}\nsphere {{ center 0 0 5 }}\n", CAMERA), 3, 1, "`sphere` requires `radius`");
+        assert_error("camera {\n    field_of_view 60\n}\n", 1, 1, "`camera` requires `image_dimensions`");
+    }
+
+    #[test]
+    fn reports_an_invalid_number() {
+        assert_error(&format!("{}sphere {{ center 0 zero 5 radius 1 }}\n", CAMERA), 2, 19, "invalid number `zero`");
+    }
+
+    #[test]
+    fn reports_a_zero_image_dimension() {
+        assert_error("camera {\n    image_dimensions 0 480\n    field_of_view 60\n}\n", 2, 22, "`image_dimensions` must be positive");
+        assert_error("camera { image_dimensions 640 0 field_of_view 60 }\n", 1, 31, "`image_dimensions` must be positive");
+    }
+
+    #[test]
+    fn reports_the_end_of_the_file_after_the_last_token() {
+        assert_error(&format!("{}sphere {{ center 0 0", CAMERA), 2, 20, "unexpected end of file");
+    }
+
+    #[test]
+    fn reports_a_missing_camera() {
+        assert_error("sphere { center 0 0 5 radius 1 }\n", 1, 1, "scene does not contain a `camera` block");
+    }
+}
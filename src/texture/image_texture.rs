@@ -0,0 +1,105 @@
+use std::sync::{Arc};
+
+use color::{ColorRGB};
+use geometry::{Point3D};
+use table::{Table};
+
+use super::{Texture};
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Filter {
+    Nearest,
+    Bilinear
+}
+
+// How texture coordinates outside of [0, 1] are mapped onto the image.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum WrapMode {
+    Repeat,
+    Clamp,
+    Mirror
+}
+
+impl WrapMode {
+    // Maps a texel index, which may lie outside the image, to one inside it.
+    fn apply(&self, index: i64, size: usize) -> usize {
+        let size = size as i64;
+        let wrapped = match *self {
+            WrapMode::Repeat => ((index % size) + size) % size,
+            WrapMode::Clamp => index.min(size - 1).max(0),
+            WrapMode::Mirror => {
+                let offset = ((index % (2 * size)) + 2 * size) % (2 * size);
+                if offset < size {
+                    offset
+                } else {
+                    2 * size - 1 - offset
+                }
+            }
+        };
+        wrapped as usize
+    }
+}
+
+// Maps an image onto a surface. The image may be shared between textures.
+// `u` runs from the left edge of the image to the right one and `v` from the
+// bottom edge to the top one.
+#[derive(Clone, Debug)]
+pub struct ImageTexture {
+    image: Arc<Table<ColorRGB>>,
+    filter: Filter,
+    wrap_mode: WrapMode
+}
+
+impl ImageTexture {
+    pub fn new(image: Arc<Table<ColorRGB>>, filter: Filter, wrap_mode: WrapMode) -> ImageTexture {
+        ImageTexture {
+            image: image,
+            filter: filter,
+            wrap_mode: wrap_mode
+        }
+    }
+
+    pub fn get_filter(&self) -> Filter {
+        self.filter
+    }
+
+    pub fn get_wrap_mode(&self) -> WrapMode {
+        self.wrap_mode
+    }
+
+    fn get_texel(&self, column: i64, row: i64) -> &ColorRGB {
+        let (width, height) = self.image.get_dimensions();
+        &self.image[(self.wrap_mode.apply(row, height), self.wrap_mode.apply(column, width))]
+    }
+}
+
+impl Texture for ImageTexture {
+    fn get_color(&self, uv: (f32, f32), _: &Point3D) -> ColorRGB {
+        let (width, height) = self.image.get_dimensions();
+        if width == 0 || height == 0 {
+            return ColorRGB::black().clone();
+        }
+
+        // Position in texels, with texel centers at half-integer positions.
+        let (u, v) = uv;
+        let x = u * width as f32;
+        let y = (1.0 - v) * height as f32;
+
+        match self.filter {
+            Filter::Nearest => self.get_texel(x.floor() as i64, y.floor() as i64).clone(),
+            Filter::Bilinear => {
+                let x = x - 0.5;
+                let y = y - 0.5;
+                let column = x.floor();
+                let row = y.floor();
+                let fx = x - column;
+                let fy = y - row;
+                let (column, row) = (column as i64, row as i64);
+
+                let top = self.get_texel(column, row) * (1.0 - fx) + self.get_texel(column + 1, row) * fx;
+                let bottom = self.get_texel(column, row + 1) * (1.0 - fx) + self.get_texel(column + 1, row + 1) * fx;
+                top * (1.0 - fy) + bottom * fy
+            }
+        }
+    }
+}
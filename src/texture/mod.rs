@@ -0,0 +1,18 @@
+use std::fmt::{Debug};
+
+use color::{ColorRGB};
+use geometry::{Point3D};
+
+pub use self::image_texture::{ImageTexture, Filter, WrapMode};
+pub use self::noise::{Perlin};
+pub use self::procedural::{Space, Mapping, Checker, Stripes, Gradient, Noise, Turbulence, Marble, Wood};
+
+mod image_texture;
+mod noise;
+mod procedural;
+
+// Varies a property of a material over a surface. Textures are looked up by
+// the texture coordinates of the hit, and may also use the point itself.
+pub trait Texture: Debug + Sync + Send {
+    fn get_color(&self, uv: (f32, f32), point: &Point3D) -> ColorRGB;
+}
@@ -0,0 +1,99 @@
+use geometry::{Point3D};
+
+// Ken Perlin's improved gradient noise. The permutation of the lattice is
+// shuffled from a seed, so different seeds give unrelated noise.
+#[derive(Clone, Debug)]
+pub struct Perlin {
+    permutation: Vec<usize>
+}
+
+impl Perlin {
+    pub fn new(seed: u32) -> Perlin {
+        let mut values = (0..256).collect::<Vec<usize>>();
+
+        // Fisher-Yates shuffle driven by a xorshift generator, whose state
+        // must not be zero.
+        let mut state = (seed as u64) ^ 0x9e37_79b9_7f4a_7c15;
+        for i in (1..values.len()).rev() {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            values.swap(i, (state % (i as u64 + 1)) as usize);
+        }
+
+        // Doubled so that sums of two entries can be looked up directly.
+        let permutation = values.iter().chain(values.iter()).cloned().collect();
+        Perlin {
+            permutation: permutation
+        }
+    }
+
+    // Smooth noise in about [-1, 1] that is zero at points with integer
+    // coordinates and varies over distances of about one unit.
+    pub fn noise(&self, point: &Point3D) -> f32 {
+        let p = &self.permutation;
+        let (x0, y0, z0) = (point.x.floor(), point.y.floor(), point.z.floor());
+        let (ix, iy, iz) = (lattice_index(x0), lattice_index(y0), lattice_index(z0));
+        let (x, y, z) = (point.x - x0, point.y - y0, point.z - z0);
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let a = p[ix] + iy;
+        let aa = p[a] + iz;
+        let ab = p[a + 1] + iz;
+        let b = p[ix + 1] + iy;
+        let ba = p[b] + iz;
+        let bb = p[b + 1] + iz;
+
+        lerp(w,
+            lerp(v,
+                lerp(u, gradient(p[aa], x, y, z), gradient(p[ba], x - 1.0, y, z)),
+                lerp(u, gradient(p[ab], x, y - 1.0, z), gradient(p[bb], x - 1.0, y - 1.0, z))
+            ),
+            lerp(v,
+                lerp(u, gradient(p[aa + 1], x, y, z - 1.0), gradient(p[ba + 1], x - 1.0, y, z - 1.0)),
+                lerp(u, gradient(p[ab + 1], x, y - 1.0, z - 1.0), gradient(p[bb + 1], x - 1.0, y - 1.0, z - 1.0))
+            )
+        )
+    }
+
+    // Sum of `octaves` layers of the absolute noise, each at twice the
+    // frequency and half the amplitude of the one before. The result is
+    // between 0 and about 1.
+    pub fn turbulence(&self, point: &Point3D, octaves: u32) -> f32 {
+        let mut sum = 0.0;
+        let mut frequency = 1.0;
+        for _ in 0..octaves {
+            let scaled = Point3D::from_xyz(point.x * frequency, point.y * frequency, point.z * frequency);
+            sum += self.noise(&scaled).abs() / frequency;
+            frequency *= 2.0;
+        }
+        sum
+    }
+}
+
+fn lattice_index(value: f32) -> usize {
+    (value as i64 & 255) as usize
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f32, a: f32, b: f32) -> f32 {
+    a + t * (b - a)
+}
+
+// Dot product of the offset with one of twelve gradient directions picked by
+// the hash.
+fn gradient(hash: usize, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
@@ -0,0 +1,257 @@
+use std::f32::consts::{PI};
+
+use color::{ColorRGB};
+use geometry::{Point3D, Vector3D, Direction3D};
+
+use super::{Texture};
+use super::noise::{Perlin};
+
+// Where a procedural texture is evaluated: on the surface, at the texture
+// coordinates (u, v, 0), or in space, at the point that was hit.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Space {
+    Surface,
+    Solid
+}
+
+// Places a pattern on objects. `scale` is the size of one repetition of the
+// pattern, like one square of a checkerboard.
+#[derive(Clone, Debug)]
+pub struct Mapping {
+    space: Space,
+    scale: f32
+}
+
+impl Mapping {
+    pub fn new(space: Space, scale: f32) -> Mapping {
+        Mapping {
+            space: space,
+            scale: scale
+        }
+    }
+
+    pub fn get_space(&self) -> Space {
+        self.space
+    }
+
+    pub fn get_scale(&self) -> f32 {
+        self.scale
+    }
+
+    fn get_position(&self, uv: (f32, f32), point: &Point3D) -> Point3D {
+        let (x, y, z) = match self.space {
+            Space::Surface => (uv.0, uv.1, 0.0),
+            Space::Solid => (point.x, point.y, point.z)
+        };
+        Point3D::from_xyz(x / self.scale, y / self.scale, z / self.scale)
+    }
+}
+
+// Alternates between two colors on a grid of unit squares or cubes.
+#[derive(Clone, Debug)]
+pub struct Checker {
+    mapping: Mapping,
+    colors: (ColorRGB, ColorRGB)
+}
+
+impl Checker {
+    pub fn new(mapping: &Mapping, colors: &(ColorRGB, ColorRGB)) -> Checker {
+        Checker {
+            mapping: mapping.clone(),
+            colors: colors.clone()
+        }
+    }
+}
+
+impl Texture for Checker {
+    fn get_color(&self, uv: (f32, f32), point: &Point3D) -> ColorRGB {
+        let position = self.mapping.get_position(uv, point);
+        let cell = position.x.floor() as i64 + position.y.floor() as i64 + position.z.floor() as i64;
+        if cell % 2 == 0 {
+            self.colors.0.clone()
+        } else {
+            self.colors.1.clone()
+        }
+    }
+}
+
+// Alternates between two colors in bands across `direction`.
+#[derive(Clone, Debug)]
+pub struct Stripes {
+    mapping: Mapping,
+    direction: Direction3D,
+    colors: (ColorRGB, ColorRGB)
+}
+
+impl Stripes {
+    pub fn new(mapping: &Mapping, direction: &Direction3D, colors: &(ColorRGB, ColorRGB)) -> Stripes {
+        Stripes {
+            mapping: mapping.clone(),
+            direction: direction.clone(),
+            colors: colors.clone()
+        }
+    }
+}
+
+impl Texture for Stripes {
+    fn get_color(&self, uv: (f32, f32), point: &Point3D) -> ColorRGB {
+        let position = self.mapping.get_position(uv, point);
+        let band = Vector3D::dot(&Vector3D::from_point(&position), &self.direction).floor() as i64;
+        if band % 2 == 0 {
+            self.colors.0.clone()
+        } else {
+            self.colors.1.clone()
+        }
+    }
+}
+
+// Fades from the first color to the second one along `direction`, over one
+// unit from the origin.
+#[derive(Clone, Debug)]
+pub struct Gradient {
+    mapping: Mapping,
+    direction: Direction3D,
+    colors: (ColorRGB, ColorRGB)
+}
+
+impl Gradient {
+    pub fn new(mapping: &Mapping, direction: &Direction3D, colors: &(ColorRGB, ColorRGB)) -> Gradient {
+        Gradient {
+            mapping: mapping.clone(),
+            direction: direction.clone(),
+            colors: colors.clone()
+        }
+    }
+}
+
+impl Texture for Gradient {
+    fn get_color(&self, uv: (f32, f32), point: &Point3D) -> ColorRGB {
+        let position = self.mapping.get_position(uv, point);
+        blend(&self.colors, Vector3D::dot(&Vector3D::from_point(&position), &self.direction))
+    }
+}
+
+// Perlin noise, from the first color where it is lowest to the second where
+// it is highest.
+#[derive(Clone, Debug)]
+pub struct Noise {
+    mapping: Mapping,
+    perlin: Perlin,
+    colors: (ColorRGB, ColorRGB)
+}
+
+impl Noise {
+    pub fn new(mapping: &Mapping, seed: u32, colors: &(ColorRGB, ColorRGB)) -> Noise {
+        Noise {
+            mapping: mapping.clone(),
+            perlin: Perlin::new(seed),
+            colors: colors.clone()
+        }
+    }
+}
+
+impl Texture for Noise {
+    fn get_color(&self, uv: (f32, f32), point: &Point3D) -> ColorRGB {
+        let position = self.mapping.get_position(uv, point);
+        blend(&self.colors, 0.5 + 0.5 * self.perlin.noise(&position))
+    }
+}
+
+// Several octaves of the absolute value of Perlin noise, which looks like
+// clouds or smoke.
+#[derive(Clone, Debug)]
+pub struct Turbulence {
+    mapping: Mapping,
+    perlin: Perlin,
+    octaves: u32,
+    colors: (ColorRGB, ColorRGB)
+}
+
+impl Turbulence {
+    pub fn new(mapping: &Mapping, seed: u32, octaves: u32, colors: &(ColorRGB, ColorRGB)) -> Turbulence {
+        Turbulence {
+            mapping: mapping.clone(),
+            perlin: Perlin::new(seed),
+            octaves: octaves,
+            colors: colors.clone()
+        }
+    }
+}
+
+impl Texture for Turbulence {
+    fn get_color(&self, uv: (f32, f32), point: &Point3D) -> ColorRGB {
+        let position = self.mapping.get_position(uv, point);
+        blend(&self.colors, self.perlin.turbulence(&position, self.octaves))
+    }
+}
+
+// Veins across `direction`, one per unit, bent by turbulence. The veins have
+// the second color.
+#[derive(Clone, Debug)]
+pub struct Marble {
+    mapping: Mapping,
+    perlin: Perlin,
+    octaves: u32,
+    direction: Direction3D,
+    turbulence: f32,
+    colors: (ColorRGB, ColorRGB)
+}
+
+impl Marble {
+    pub fn new(mapping: &Mapping, seed: u32, octaves: u32, direction: &Direction3D, turbulence: f32, colors: &(ColorRGB, ColorRGB)) -> Marble {
+        Marble {
+            mapping: mapping.clone(),
+            perlin: Perlin::new(seed),
+            octaves: octaves,
+            direction: direction.clone(),
+            turbulence: turbulence,
+            colors: colors.clone()
+        }
+    }
+}
+
+impl Texture for Marble {
+    fn get_color(&self, uv: (f32, f32), point: &Point3D) -> ColorRGB {
+        let position = self.mapping.get_position(uv, point);
+        let offset = Vector3D::dot(&Vector3D::from_point(&position), &self.direction) +
+            self.turbulence * self.perlin.turbulence(&position, self.octaves);
+        blend(&self.colors, 0.5 - 0.5 * (2.0 * PI * offset).cos())
+    }
+}
+
+// Growth rings around the y axis, one per unit, made irregular by
+// turbulence. Each ring fades from the first color to the second.
+#[derive(Clone, Debug)]
+pub struct Wood {
+    mapping: Mapping,
+    perlin: Perlin,
+    octaves: u32,
+    turbulence: f32,
+    colors: (ColorRGB, ColorRGB)
+}
+
+impl Wood {
+    pub fn new(mapping: &Mapping, seed: u32, octaves: u32, turbulence: f32, colors: &(ColorRGB, ColorRGB)) -> Wood {
+        Wood {
+            mapping: mapping.clone(),
+            perlin: Perlin::new(seed),
+            octaves: octaves,
+            turbulence: turbulence,
+            colors: colors.clone()
+        }
+    }
+}
+
+impl Texture for Wood {
+    fn get_color(&self, uv: (f32, f32), point: &Point3D) -> ColorRGB {
+        let position = self.mapping.get_position(uv, point);
+        let radius = (position.x * position.x + position.z * position.z).sqrt() +
+            self.turbulence * self.perlin.turbulence(&position, self.octaves);
+        blend(&self.colors, radius - radius.floor())
+    }
+}
+
+fn blend(colors: &(ColorRGB, ColorRGB), t: f32) -> ColorRGB {
+    let t = t.clamp(0.0, 1.0);
+    &colors.0 * (1.0 - t) + &colors.1 * t
+}